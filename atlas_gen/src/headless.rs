@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use atlas_lib::{
+    config::{gen::AtlasGenConfig, load_config, AtlasConfig},
+    domain::{graphics::MapLogicData, map::MAP_DATA_LAYERS},
+};
+
+use crate::map::{export_world, generate_all};
+
+/// Command line flag that enables the headless mode.
+pub const HEADLESS_FLAG: &str = "--headless";
+
+const USAGE: &str = "Usage: atlas_gen --headless <path to atlasgen.toml> <output directory>";
+
+/// Run the generator without a window: load config, generate all layers and export the world.
+/// Returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let (config_path, output_path) = match args {
+        [config, output] => (PathBuf::from(config), PathBuf::from(output)),
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+    match generate_and_export(&config_path, &output_path) {
+        Ok(()) => {
+            println!("World exported to {}", output_path.display());
+            0
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            1
+        }
+    }
+}
+
+/// Helper function
+///
+/// Do the actual work of the headless mode.
+fn generate_and_export(config_path: &Path, output_path: &Path) -> Result<(), String> {
+    let mut config: AtlasGenConfig = load_config(config_path).map_err(|x| x.to_string())?;
+    // Prepare all layers, just like the map plugin does on startup.
+    let mut logics = MapLogicData::default();
+    for layer in MAP_DATA_LAYERS {
+        logics.put_layer(layer, vec![]);
+    }
+    let (width, height) = config.get_world_size();
+    logics.resize_all_layers((width * height) as usize);
+    logics.load_climatemap().map_err(|x| x.to_string())?;
+    // Generate everything.
+    generate_all(&mut logics, &mut config);
+    // Export.
+    std::fs::create_dir_all(output_path).map_err(|x| x.to_string())?;
+    export_world(output_path, &logics, &config).map_err(|x| x.to_string())
+}
@@ -4,15 +4,19 @@
 use atlas_lib::{
     base::events::EventPlugin,
     bevy::{core::TaskPoolThreadAssignmentPolicy, prelude::*},
-    bevy_prng::WyRand,
-    bevy_rand::plugin::EntropyPlugin,
     config::{gen::AtlasGenConfig, ConfigPlugin},
 };
+mod headless;
 mod map;
 mod ui;
 
 /// Application entry point.
 fn main() {
+    // Run without a window if requested.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).is_some_and(|x| x == headless::HEADLESS_FLAG) {
+        std::process::exit(headless::run(&args[2..]));
+    }
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -34,7 +38,6 @@ fn main() {
                         ..Default::default()
                     },
                 }),
-            EntropyPlugin::<WyRand>::default(),
             ConfigPlugin::<AtlasGenConfig>::default(),
             EventPlugin,
             ui::UiPlugin,
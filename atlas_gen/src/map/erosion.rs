@@ -0,0 +1,183 @@
+use atlas_lib::{
+    bevy::math::Vec2,
+    bevy_prng::WyRand,
+    config::{gen::ErosionConfig, WorldModel, NEIGHBOURS_4},
+    rand::{Rng, SeedableRng},
+};
+
+/// Max number of steps a single droplet can take.
+const MAX_DROPLET_STEPS: usize = 64;
+/// Speed gained by a droplet when going down by one height unit.
+const GRAVITY: f32 = 4.0;
+/// Minimum slope used for sediment capacity, so that droplets on flat terrain still carry something.
+const MIN_SLOPE: f32 = 0.01;
+/// Fraction of excess height difference moved per thermal erosion iteration.
+const THERMAL_RATE: f32 = 0.5;
+
+/// Height map stored as floats, for precise erosion.
+struct HeightMap {
+    data: Vec<f32>,
+    width: usize,
+    height: usize,
+    model: WorldModel,
+}
+
+impl HeightMap {
+    /// Get indices of the four tiles around a point, respecting the world model.
+    /// Returns `None` if the point is outside the map or too close to its edge.
+    fn corners(&self, p: Vec2) -> Option<([usize; 4], f32, f32)> {
+        let (x, y) = (p.x.floor(), p.y.floor());
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        let size = (self.width as u32, self.height as u32);
+        let i = y as u32 * size.0 + x as u32;
+        let mut corners = [0; 4];
+        for (corner, offset) in corners.iter_mut().zip([(0, 0), (1, 0), (0, 1), (1, 1)]) {
+            *corner = self.model.offset_tile(size, i, offset)? as usize;
+        }
+        Some((corners, p.x - x, p.y - y))
+    }
+
+    /// Move a point back onto the map if it crossed a wrapping edge.
+    /// Returns the new point and whether it crossed a pole (its north-south direction is reversed).
+    fn wrap(&self, p: Vec2) -> (Vec2, bool) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        match self.model {
+            WorldModel::Flat => (p, false),
+            WorldModel::Globe => {
+                let (x, y, flip) = if p.y < 0.0 {
+                    (p.x + width / 2.0, -1.0 - p.y, true)
+                } else if p.y >= height {
+                    (p.x + width / 2.0, 2.0 * height - 1.0 - p.y, true)
+                } else {
+                    (p.x, p.y, false)
+                };
+                (Vec2::new(x.rem_euclid(width), y), flip)
+            }
+        }
+    }
+
+    /// Get interpolated height and gradient at a point.
+    fn sample(&self, p: Vec2) -> Option<(f32, Vec2)> {
+        let ([i00, i10, i01, i11], u, v) = self.corners(p)?;
+        let (h00, h10, h01, h11) = (self.data[i00], self.data[i10], self.data[i01], self.data[i11]);
+        let gradient = Vec2::new(
+            (h10 - h00) * (1.0 - v) + (h11 - h01) * v,
+            (h01 - h00) * (1.0 - u) + (h11 - h10) * u,
+        );
+        let height = h00 * (1.0 - u) * (1.0 - v) + h10 * u * (1.0 - v) + h01 * (1.0 - u) * v + h11 * u * v;
+        Some((height, gradient))
+    }
+
+    /// Add (or remove) material at a point, distributed between four closest tiles.
+    fn add(&mut self, p: Vec2, amount: f32) {
+        let Some(([i00, i10, i01, i11], u, v)) = self.corners(p) else {
+            return;
+        };
+        self.data[i00] += amount * (1.0 - u) * (1.0 - v);
+        self.data[i10] += amount * u * (1.0 - v);
+        self.data[i01] += amount * (1.0 - u) * v;
+        self.data[i11] += amount * u * v;
+    }
+}
+
+/// Erode topography data with simulated water droplets and (optionally) thermal weathering.
+pub fn erode(data: &mut [f32], config: &ErosionConfig, world_size: [u32; 2], model: WorldModel) {
+    let (width, height) = (world_size[0] as usize, world_size[1] as usize);
+    if (config.iterations == 0 && config.thermal_iterations == 0) || width < 2 || height < 2 {
+        return;
+    }
+    let mut map = HeightMap {
+        data: data.to_vec(),
+        width,
+        height,
+        model,
+    };
+    erode_hydraulic(&mut map, config);
+    erode_thermal(&mut map, config);
+    for (value, new) in data.iter_mut().zip(map.data) {
+        *value = new.clamp(0.0, 255.0);
+    }
+}
+
+/// Simulate droplets that pick up sediment when flowing downhill and drop it when slowing down.
+fn erode_hydraulic(map: &mut HeightMap, config: &ErosionConfig) {
+    let mut rng = WyRand::seed_from_u64(config.seed as u64);
+    let (max_x, max_y) = ((map.width - 1) as f32, (map.height - 1) as f32);
+    for _ in 0..config.iterations {
+        let mut pos = Vec2::new(rng.gen_range(0.0..max_x), rng.gen_range(0.0..max_y));
+        let mut dir = Vec2::ZERO;
+        let (mut speed, mut water, mut sediment) = (1.0f32, 1.0f32, 0.0f32);
+        for _ in 0..MAX_DROPLET_STEPS {
+            let Some((old_height, gradient)) = map.sample(pos) else {
+                break;
+            };
+            // Flow downhill, keeping some of the previous direction.
+            dir = (dir * config.inertia - gradient * (1.0 - config.inertia)).normalize_or_zero();
+            if dir == Vec2::ZERO {
+                break;
+            }
+            let (new_pos, flip) = map.wrap(pos + dir);
+            let Some((new_height, _)) = map.sample(new_pos) else {
+                break;
+            };
+            let delta = new_height - old_height;
+            let capacity = (-delta).max(MIN_SLOPE) * speed * water * config.capacity;
+            if delta > 0.0 || sediment > capacity {
+                // Fill the pit when going uphill, otherwise drop excess sediment.
+                let amount = if delta > 0.0 {
+                    delta.min(sediment)
+                } else {
+                    (sediment - capacity) * config.deposition_rate
+                };
+                sediment -= amount;
+                map.add(pos, amount);
+            } else {
+                // Don't dig deeper than the height difference.
+                let amount = ((capacity - sediment) * config.erosion_rate).min(-delta);
+                sediment += amount;
+                map.add(pos, -amount);
+            }
+            speed = (speed * speed - delta * GRAVITY).max(0.0).sqrt();
+            water *= 1.0 - config.evaporation_rate;
+            pos = new_pos;
+            if flip {
+                dir.y = -dir.y;
+            }
+        }
+    }
+}
+
+/// Move material from steep slopes to lower neighbours until slopes are below the talus threshold.
+fn erode_thermal(map: &mut HeightMap, config: &ErosionConfig) {
+    let size = (map.width as u32, map.height as u32);
+    let mut delta = vec![0.0; map.data.len()];
+    for _ in 0..config.thermal_iterations {
+        delta.fill(0.0);
+        for i in 0..map.data.len() {
+            // Find the lowest neighbour.
+            let mut lowest = None;
+            let mut max_diff = config.talus;
+            for offset in NEIGHBOURS_4 {
+                let Some(j) = map.model.offset_tile(size, i as u32, offset) else {
+                    continue;
+                };
+                let j = j as usize;
+                let diff = map.data[i] - map.data[j];
+                if diff > max_diff {
+                    max_diff = diff;
+                    lowest = Some(j);
+                }
+            }
+            if let Some(j) = lowest {
+                let amount = (max_diff - config.talus) * THERMAL_RATE / 2.0;
+                delta[i] -= amount;
+                delta[j] += amount;
+            }
+        }
+        for (value, delta) in map.data.iter_mut().zip(delta.iter()) {
+            *value += delta;
+        }
+    }
+}
@@ -1,24 +1,32 @@
+use std::path::Path;
+
 use atlas_lib::{
     base::{events::EventStruct, map::resize_helper},
     bevy::prelude::*,
-    bevy_prng::WyRand,
-    bevy_rand::resource::GlobalEntropy,
     config::{
+        climate::{celsius_from_byte, precip_from_byte, ALTITUDE_STEP},
+        custom::check_custom_layers,
         gen::{AtlasGenConfig, CONFIG_NAME as CONFIG_NAME_GEN},
-        load_config, load_image, load_image_grey, save_config, save_image, save_image_grey,
+        load_config, load_image, load_image_grey, load_image_grey_precise, save_ascii_grid, save_config,
+        save_geotiff, save_image, save_image_grey, save_image_grey16, save_raw16,
         sim::CONFIG_NAME as CONFIG_NAME_SIM,
-        AtlasConfig,
+        AtlasConfig, Error, GeoTransform,
     },
     domain::{
         graphics::{
             get_material, MapGraphicsData, MapLogicData, WorldGlobeMesh, WorldMapMesh, CLIMATEMAP_NAME,
             CLIMATEMAP_SIZE,
         },
-        map::{MapDataLayer, EXPORT_DATA_LAYERS},
+        map::{
+            MapDataLayer, EXPORT_DATA_LAYERS, EXPORT_GIS_LAYERS, EXPORT_PRECISE_LAYERS, EXPORT_RAW_HEIGHTMAP,
+        },
     },
 };
 
-use crate::map::generation::{after_generate, generate};
+use crate::map::{
+    mesh::export_mesh,
+    pipeline::{after_generate, generate},
+};
 
 /// Make a run condition function.
 macro_rules! rc {
@@ -31,6 +39,8 @@ pub fn $fun(events: Res<EventStruct>) -> bool {
 
 // Check if "load layer data" event needs handling.
 rc!(check_event_loaded, load_layer_request);
+// Check if "load high precision layer data" event needs handling.
+rc!(check_event_loaded_precise, load_layer_precise_request);
 // Check if "save layer data" event needs handling.
 rc!(check_event_saved, save_layer_request);
 // Check if "save layer image" event needs handling.
@@ -39,12 +49,18 @@ pub fn $fun(events: Res<EventStruct>) -> bool {
 rc!(check_event_clear, clear_layer_request);
 // Check if "generate layer data" event needs handling.
 rc!(check_event_generate, generate_request);
+// Check if "brush stroke ended" event needs handling.
+rc!(check_event_brush, brush_stroke_request);
 // Check if "reload climatemap.png" event needs handling.
 rc!(check_event_climatemap, load_climatemap_request);
 // Check if "import world" event needs handling.
 rc!(check_event_import, import_world_request);
 // Check if "export world" event needs handling.
 rc!(check_event_export, export_world_request);
+// Check if "export world as GIS rasters" event needs handling.
+rc!(check_event_export_gis, export_gis_request);
+// Check if "export terrain mesh" event needs handling.
+rc!(check_event_export_mesh, export_mesh_request);
 
 /// Update system
 ///
@@ -53,20 +69,38 @@ pub fn update_event_loaded(
     mut events: ResMut<EventStruct>,
     mut logics: ResMut<MapLogicData>,
     mut config: ResMut<AtlasGenConfig>,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
 ) {
     let (layer, data) = events.load_layer_request.take().expect("Always Some");
+    // Influence images are only used by "from image" shapes, the influence map is regenerated from them.
+    let regen_layers = if layer.get_influence_layer() == Some(layer) {
+        logics.put_image(layer, data);
+        generate(layer, &mut logics, &mut config)
+    } else if let MapDataLayer::Custom(_) = layer {
+        // Custom layers have no influence map, so their image is also kept for "from image" shapes.
+        logics.put_image(layer, data.clone());
+        logics.put_layer(layer, data);
+        vec![layer]
+    } else {
+        logics.put_layer(layer, data);
+        vec![layer]
+    };
+    // Handle post generation, which refreshes the texture and dependant layers.
+    post_generation(layer, &mut logics, &mut events, &mut config, regen_layers);
+}
+
+/// Update system
+///
+/// Load new high precision layer data.
+pub fn update_event_loaded_precise(
+    mut events: ResMut<EventStruct>,
+    mut logics: ResMut<MapLogicData>,
+    mut config: ResMut<AtlasGenConfig>,
+) {
+    let (layer, data) = events.load_layer_precise_request.take().expect("Always Some");
     // Assign data.
-    logics.put_layer(layer, data);
+    logics.put_layer_precise(layer, data);
     // Handle post generation, which refreshes the texture and dependant layers.
-    post_generation(
-        layer,
-        &mut logics,
-        &mut events,
-        &mut config,
-        vec![layer],
-        &mut rng,
-    );
+    post_generation(layer, &mut logics, &mut events, &mut config, vec![layer]);
 }
 
 /// Update system
@@ -143,27 +177,32 @@ pub fn update_event_generate(
     mut events: ResMut<EventStruct>,
     mut logics: ResMut<MapLogicData>,
     mut config: ResMut<AtlasGenConfig>,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
 ) {
     let (layer, regen_influence) = events.generate_request.take().expect("Always Some");
     let mut regen_layers: Vec<MapDataLayer> = vec![];
     // If this layer has an associated influence layer, forcefully regenerate it as well.
     if regen_influence {
         if let Some(layer2) = layer.get_influence_layer() {
-            regen_layers.extend(generate(layer2, &mut logics, &mut config, &mut rng));
+            regen_layers.extend(generate(layer2, &mut logics, &mut config));
         }
     }
     // Run generation procedure based on generator type and layer.
-    regen_layers.extend(generate(layer, &mut logics, &mut config, &mut rng));
+    regen_layers.extend(generate(layer, &mut logics, &mut config));
     // Handle post generation.
-    post_generation(
-        layer,
-        &mut logics,
-        &mut events,
-        &mut config,
-        regen_layers,
-        &mut rng,
-    );
+    post_generation(layer, &mut logics, &mut events, &mut config, regen_layers);
+}
+
+/// Update system
+///
+/// Refresh dependant layers after a brush stroke.
+pub fn update_event_brush(
+    mut events: ResMut<EventStruct>,
+    mut logics: ResMut<MapLogicData>,
+    mut config: ResMut<AtlasGenConfig>,
+) {
+    let layer = events.brush_stroke_request.take().expect("Always Some");
+    // Handle post generation, which refreshes the texture and dependant layers.
+    post_generation(layer, &mut logics, &mut events, &mut config, vec![layer]);
 }
 
 /// Update system
@@ -177,7 +216,7 @@ pub fn update_event_climatemap(mut events: ResMut<EventStruct>, mut logics: ResM
 
 /// Update system
 ///
-/// Import the world: preview, all layers, config, and climate map.
+/// Import the world: preview, all layers (including custom layers), config, and climate map.
 pub fn update_event_import(
     mut events: ResMut<EventStruct>,
     mut logics: ResMut<MapLogicData>,
@@ -206,6 +245,8 @@ pub fn update_event_import(
         let path = base_path.join(name);
         let result = match layer {
             MapDataLayer::Preview => load_image(path, width, height),
+            // Worlds exported before rivers were added have no rivers.
+            MapDataLayer::Rivers if !path.exists() => Ok(vec![0; (width * height) as usize]),
             _ => load_image_grey(path, width, height),
         };
         match result {
@@ -219,6 +260,39 @@ pub fn update_event_import(
             }
         };
     }
+    // Import high precision layers, if they were exported.
+    for (layer, name) in EXPORT_PRECISE_LAYERS {
+        let path = base_path.join(name);
+        if !path.exists() {
+            continue;
+        }
+        match load_image_grey_precise(path, width, height) {
+            Ok(data) => logics.put_layer_precise(layer, data),
+            Err(error) => {
+                events.error_window = Some(error.to_string());
+                return;
+            }
+        };
+    }
+    // Import custom layers.
+    let custom_layers = config.get_custom_layers();
+    if let Err(error) = check_custom_layers(&custom_layers) {
+        events.error_window = Some(error.to_string());
+        return;
+    }
+    for (i, info) in custom_layers.iter().enumerate() {
+        let layer = MapDataLayer::Custom(i as u8);
+        match load_image_grey(base_path.join(info.file_name()), width, height) {
+            Ok(data) => {
+                logics.put_layer(layer, data);
+                regen_layers.push(layer);
+            }
+            Err(error) => {
+                events.error_window = Some(error.to_string());
+                return;
+            }
+        };
+    }
     // Import climate map.
     let path = base_path.join(CLIMATEMAP_NAME);
     match load_image_grey(path, CLIMATEMAP_SIZE as u32, CLIMATEMAP_SIZE as u32) {
@@ -236,45 +310,56 @@ pub fn update_event_import(
 
 /// Update system
 ///
-/// Export the world: preview, all layers, config, and climate map.
+/// Export the world: preview, all layers (including custom layers), config, and climate map.
 pub fn update_event_export(
     mut events: ResMut<EventStruct>,
     logics: ResMut<MapLogicData>,
     config: Res<AtlasGenConfig>,
 ) {
     let base_path = events.export_world_request.take().expect("Always Some");
+    let result = export_world(&base_path, &logics, &config);
+    events.error_window = result.err().map(|x| x.to_string());
+}
+
+/// Helper function
+///
+/// Write all exported layers, climate map and both configs to a directory.
+pub fn export_world(base_path: &Path, logics: &MapLogicData, config: &AtlasGenConfig) -> Result<(), Error> {
+    // Custom layers must not overwrite each other's files.
+    let custom_layers = config.get_custom_layers();
+    check_custom_layers(&custom_layers)?;
     // Export all layers.
     let (width, height) = (config.general.world_size[0], config.general.world_size[1]);
     for (layer, name) in EXPORT_DATA_LAYERS {
         let data = logics.get_layer(layer);
         let path = base_path.join(name);
-        let result = match layer {
+        match layer {
             MapDataLayer::Preview => save_image(path, data, width, height),
             _ => save_image_grey(path, data, width, height),
-        };
-        events.error_window = result.err().map(|x| x.to_string());
-        if events.error_window.is_some() {
-            return;
-        }
+        }?;
+    }
+    // Export high precision layers.
+    for (layer, name) in EXPORT_PRECISE_LAYERS {
+        let data = logics.get_layer_precise(layer);
+        let path = base_path.join(name);
+        save_image_grey16(path, &data, width, height)?;
+    }
+    let data = logics.get_layer_precise(MapDataLayer::RealTopography);
+    save_raw16(base_path.join(EXPORT_RAW_HEIGHTMAP), &data)?;
+    // Export custom layers.
+    for (i, info) in custom_layers.iter().enumerate() {
+        let data = logics.get_layer(MapDataLayer::Custom(i as u8));
+        save_image_grey(base_path.join(info.file_name()), data, width, height)?;
     }
     // Export climate map.
     let climatemap = logics.get_climatemap();
     let path = base_path.join(CLIMATEMAP_NAME);
-    let result = save_image_grey(path, climatemap, CLIMATEMAP_SIZE as u32, CLIMATEMAP_SIZE as u32);
-    events.error_window = result.err().map(|x| x.to_string());
-    if events.error_window.is_some() {
-        return;
-    }
+    save_image_grey(path, climatemap, CLIMATEMAP_SIZE as u32, CLIMATEMAP_SIZE as u32)?;
     // Export config.
     let path = base_path.join(CONFIG_NAME_GEN);
-    let result = save_config(config.as_ref(), path);
-    events.error_window = result.err().map(|x| x.to_string());
-    if events.error_window.is_some() {
-        return;
-    }
+    save_config(config, path)?;
     let path = base_path.join(CONFIG_NAME_SIM);
-    let result = save_config(&config.into_sim_config(), path);
-    events.error_window = result.err().map(|x| x.to_string());
+    save_config(&config.into_sim_config(), path)
 }
 
 /// Helper function
@@ -286,11 +371,76 @@ fn post_generation(
     events: &mut EventStruct,
     config: &mut AtlasGenConfig,
     mut regen_layers: Vec<MapDataLayer>,
-    rng: &mut GlobalEntropy<WyRand>,
 ) {
     // Adjust other layers if needed.
-    let regen_layers_2 = after_generate(layer, logics, config, rng);
+    let regen_layers_2 = after_generate(layer, logics, config);
     regen_layers.extend(regen_layers_2);
     // Trigger texture regeneration.
     events.regen_layer_request = Some(regen_layers);
 }
+
+/// Update system
+///
+/// Export all numeric layers as GIS rasters.
+pub fn update_event_export_gis(
+    mut events: ResMut<EventStruct>,
+    logics: Res<MapLogicData>,
+    config: Res<AtlasGenConfig>,
+) {
+    let base_path = events.export_gis_request.take().expect("Always Some");
+    let result = export_gis(&base_path, &logics, &config);
+    events.error_window = result.err().map(|x| x.to_string());
+}
+
+/// Helper function
+///
+/// Write all numeric layers in physical units as ESRI ASCII grids and GeoTIFFs to a directory.
+pub fn export_gis(base_path: &Path, logics: &MapLogicData, config: &AtlasGenConfig) -> Result<(), Error> {
+    let (width, height) = config.get_world_size();
+    let transform = GeoTransform::equirectangular(width, height);
+    for (layer, name) in EXPORT_GIS_LAYERS {
+        let data = get_physical_layer(logics, layer);
+        let path = base_path.join(format!("{}.asc", name));
+        save_ascii_grid(path, &data, width, height, &transform)?;
+        let path = base_path.join(format!("{}.tif", name));
+        save_geotiff(path, &data, width, height, &transform)?;
+    }
+    Ok(())
+}
+
+/// Helper function
+///
+/// Convert layer data to physical units: metres, degrees Celsius or millimetres.
+fn get_physical_layer(logics: &MapLogicData, layer: MapDataLayer) -> Vec<f32> {
+    match layer {
+        MapDataLayer::Topography | MapDataLayer::RealTopography => logics
+            .get_layer_precise(layer)
+            .iter()
+            .map(|x| x * ALTITUDE_STEP)
+            .collect(),
+        MapDataLayer::Temperature => logics
+            .get_layer(layer)
+            .iter()
+            .map(|x| celsius_from_byte(*x))
+            .collect(),
+        MapDataLayer::Precipitation => logics
+            .get_layer(layer)
+            .iter()
+            .map(|x| precip_from_byte(*x))
+            .collect(),
+        _ => logics.get_layer(layer).iter().map(|x| *x as f32).collect(),
+    }
+}
+
+/// Update system
+///
+/// Export final topography as a 3D terrain mesh.
+pub fn update_event_export_mesh(
+    mut events: ResMut<EventStruct>,
+    logics: Res<MapLogicData>,
+    config: Res<AtlasGenConfig>,
+) {
+    let base_path = events.export_mesh_request.take().expect("Always Some");
+    let result = export_mesh(&base_path, &logics, &config);
+    events.error_window = result.err().map(|x| x.to_string());
+}
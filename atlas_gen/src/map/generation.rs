@@ -1,132 +1,42 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
 use atlas_lib::{
     bevy::utils::petgraph::matrix_graph::Zero,
     bevy_prng::WyRand,
-    bevy_rand::resource::GlobalEntropy,
     config::{
         climate::{precip_clamp, precip_to_byte, ALTITUDE_STEP},
         deposit::DepositChunk,
-        gen::{AtlasGenConfig, ColorDisplayMode, InfluenceMode, InfluenceShape, NoiseAlgorithm},
-        AtlasConfig, WorldModel,
+        gen::{AtlasGenConfig, ColorDisplayMode, InfluenceConfig, InfluenceMode, NoiseAlgorithm},
+        AtlasConfig, WorldModel, NEIGHBOURS_8,
     },
     domain::{
         graphics::{MapLogicData, CLIMATEMAP_SIZE},
-        map::{is_sea, MapDataLayer},
+        map::{is_sea, MapDataLayer, RIVER_LAKE},
     },
-    rand::Rng,
+    rand::{Rng, SeedableRng},
 };
 
-use crate::map::samplers::{
-    add_with_algorithm, apply_influence, apply_influence_from_src, fill_influence, fill_latitudinal_precip,
-    fill_latitudinal_temp, fill_with_algorithm,
+use crate::map::{
+    erosion::erode,
+    oceans::{apply_continentality, apply_ocean_currents},
+    samplers::{
+        add_tectonic_relief, add_with_algorithm, apply_influence, apply_influence_from_src, fill_influence,
+        fill_latitudinal_precip, fill_latitudinal_temp, fill_with_algorithm, LayerValue,
+    },
+    winds::apply_rain_shadow,
 };
 
-/// Choose relevant generation procedure based on layer.
-pub fn generate(
-    layer: MapDataLayer,
-    logics: &mut MapLogicData,
-    config: &mut AtlasGenConfig,
-    rng: &mut GlobalEntropy<WyRand>,
-) -> Vec<MapDataLayer> {
-    let model = config.general.generation_model;
-    let world_size = config.general.world_size;
-    match layer {
-        MapDataLayer::Preview => generate_preview(logics, config),
-        MapDataLayer::Continents => generate_continents(logics, config, layer),
-        MapDataLayer::Topography => generate_generic(logics, &config.topography, model, world_size, layer),
-        MapDataLayer::Temperature => generate_temperature(logics, config, layer),
-        MapDataLayer::Precipitation => generate_precipitation(logics, config, layer),
-        MapDataLayer::Climate => generate_climate(logics, config, layer),
-        MapDataLayer::Deposits => generate_resources(logics, config, layer, rng),
-        // Influence
-        MapDataLayer::ContinentsInfluence => {
-            generate_influence(logics, &config.continents, model, world_size, layer)
-        }
-        MapDataLayer::TopographyInfluence => {
-            generate_influence(logics, &config.topography, model, world_size, layer)
-        }
-        MapDataLayer::TemperatureInfluence => {
-            generate_influence(logics, &config.temperature, model, world_size, layer)
-        }
-        MapDataLayer::PrecipitationInfluence => {
-            generate_influence(logics, &config.precipitation, model, world_size, layer)
-        }
-        // Unreachable
-        MapDataLayer::RealTopography => unreachable!(),
-        MapDataLayer::TopographyFilter => unreachable!(),
-    }
-}
-
-/// Refresh other layers (if needed) after modifying this layer.
-pub fn after_generate(
-    layer: MapDataLayer,
-    logics: &mut MapLogicData,
-    config: &mut AtlasGenConfig,
-    rng: &mut GlobalEntropy<WyRand>,
-) -> Vec<MapDataLayer> {
-    let mut regen_layers = match layer {
-        MapDataLayer::Continents => {
-            generate_utility_topo_filter(logics, config);
-            generate_utility_real_topo(logics);
-            generate_temperature(logics, config, MapDataLayer::Temperature);
-            generate_precipitation(logics, config, MapDataLayer::Precipitation);
-            generate_climate(logics, config, MapDataLayer::Climate);
-            generate_resources(logics, config, MapDataLayer::Deposits, rng);
-            vec![
-                MapDataLayer::TopographyFilter,
-                MapDataLayer::RealTopography,
-                MapDataLayer::Temperature,
-                MapDataLayer::Precipitation,
-                MapDataLayer::Climate,
-            ]
-        }
-        MapDataLayer::Topography => {
-            generate_utility_topo_filter(logics, config);
-            generate_utility_real_topo(logics);
-            generate_temperature(logics, config, MapDataLayer::Temperature);
-            generate_precipitation(logics, config, MapDataLayer::Precipitation);
-            generate_climate(logics, config, MapDataLayer::Climate);
-            generate_resources(logics, config, MapDataLayer::Deposits, rng);
-            vec![
-                MapDataLayer::TopographyFilter,
-                MapDataLayer::RealTopography,
-                MapDataLayer::Temperature,
-                MapDataLayer::Precipitation,
-                MapDataLayer::Climate,
-            ]
-        }
-        MapDataLayer::Temperature => {
-            generate_climate(logics, config, MapDataLayer::Climate);
-            generate_resources(logics, config, MapDataLayer::Deposits, rng);
-            vec![MapDataLayer::Climate]
-        }
-        MapDataLayer::Precipitation => {
-            generate_climate(logics, config, MapDataLayer::Climate);
-            generate_resources(logics, config, MapDataLayer::Deposits, rng);
-            vec![MapDataLayer::Climate]
-        }
-        MapDataLayer::Climate => {
-            generate_resources(logics, config, MapDataLayer::Deposits, rng);
-            vec![]
-        }
-        _ => vec![],
-    };
-    match layer {
-        MapDataLayer::Preview => { /* Do nothing */ }
-        _ => {
-            generate_preview(logics, config);
-            regen_layers.push(MapDataLayer::Preview);
-        }
-    }
-    regen_layers
-}
+/// Rivers layer value gained every time river discharge doubles.
+const RIVER_LOG_SCALE: f32 = 32.0;
 
 /// Generate pretty map preview.
-fn generate_preview(logics: &mut MapLogicData, config: &AtlasGenConfig) -> Vec<MapDataLayer> {
+pub fn generate_preview(logics: &mut MapLogicData, config: &AtlasGenConfig) -> Vec<MapDataLayer> {
     // Move out layer data.
     let mut preview_data = logics.pop_layer(MapDataLayer::Preview);
     let real_data = logics.get_layer(MapDataLayer::RealTopography);
     let cont_data = logics.get_layer(MapDataLayer::Continents);
     let climate_data = logics.get_layer(MapDataLayer::Climate);
+    let river_data = logics.get_layer(MapDataLayer::Rivers);
     // Get relevant config settings.
     let climate_display = config.general.color_display;
     let height_levels = config.general.height_levels as f32;
@@ -135,6 +45,8 @@ fn generate_preview(logics: &mut MapLogicData, config: &AtlasGenConfig) -> Vec<M
     for i in 0..real_data.len() {
         // Fetch preview color.
         let rgb = match climate_display {
+            // Rivers and lakes are drawn over everything else.
+            _ if !is_sea(cont_data[i]) && river_data[i] != 0 => [0, 160, 255],
             ColorDisplayMode::Topography => {
                 if is_sea(cont_data[i]) {
                     [0, 160, 255]
@@ -187,7 +99,7 @@ fn generate_preview(logics: &mut MapLogicData, config: &AtlasGenConfig) -> Vec<M
 }
 
 /// Generate continental data.
-fn generate_continents(
+pub fn generate_continents(
     logics: &mut MapLogicData,
     config: &AtlasGenConfig,
     layer: MapDataLayer,
@@ -227,13 +139,13 @@ fn generate_continents(
 /// A generic generation routine.
 fn generate_generic(
     logics: &mut MapLogicData,
-    config: impl AsRef<NoiseAlgorithm> + AsRef<InfluenceShape>,
+    config: impl AsRef<NoiseAlgorithm> + AsRef<InfluenceConfig>,
     model: WorldModel,
     world_size: [u32; 2],
     layer: MapDataLayer,
 ) -> Vec<MapDataLayer> {
-    // Move out layer data.
-    let mut data = logics.pop_layer(layer);
+    // Copy out layer data with high precision.
+    let mut data = logics.get_layer_precise(layer);
     // Run the noise algorithm for map topography (height data).
     fill_with_algorithm(&mut data, model, world_size, &config);
     // Apply the influence map if requested.
@@ -241,12 +153,32 @@ fn generate_generic(
         handle_influence(&mut data, logics, inf_layer, &config);
     }
     // Set new layer data.
-    logics.put_layer(layer, data);
+    logics.put_layer_precise(layer, data);
     // This layer should be refreshed.
     vec![layer]
 }
 
-fn generate_temperature(
+/// Generate topography, and erode it if requested.
+pub fn generate_topography(
+    logics: &mut MapLogicData,
+    config: &AtlasGenConfig,
+    layer: MapDataLayer,
+) -> Vec<MapDataLayer> {
+    let model = config.general.generation_model;
+    let world_size = config.general.world_size;
+    let regen_layers = generate_generic(logics, &config.topography, model, world_size, layer);
+    let mut data = logics.get_layer_precise(layer);
+    // Mountain chains follow plate boundaries, if continents come from tectonic plates.
+    if let NoiseAlgorithm::Tectonic(tectonic) = &config.continents.algorithm {
+        add_tectonic_relief(&mut data, model, world_size, tectonic);
+    }
+    // Erode raw topography. Coastal erosion is applied later, together with the continents.
+    erode(&mut data, &config.topography.erosion, world_size, model);
+    logics.put_layer_precise(layer, data);
+    regen_layers
+}
+
+pub fn generate_temperature(
     logics: &mut MapLogicData,
     config: &AtlasGenConfig,
     layer: MapDataLayer,
@@ -275,6 +207,23 @@ fn generate_temperature(
             temp_data[i] = temp_data[i].saturating_sub(drop);
         }
     }
+    // Apply continentality and ocean currents.
+    let cont_data = logics.get_layer(MapDataLayer::Continents);
+    apply_continentality(
+        &mut temp_data,
+        cont_data,
+        &config.temperature.continentality,
+        model,
+        world_size,
+    );
+    apply_ocean_currents(
+        &mut temp_data,
+        cont_data,
+        &config.temperature.currents,
+        &config.precipitation.rain_shadow.winds,
+        model,
+        world_size,
+    );
     // Apply the influence map if requested.
     if let Some(inf_layer) = layer.get_influence_layer() {
         handle_influence(&mut temp_data, logics, inf_layer, &config.temperature);
@@ -285,7 +234,7 @@ fn generate_temperature(
     vec![layer]
 }
 
-fn generate_precipitation(
+pub fn generate_precipitation(
     logics: &mut MapLogicData,
     config: &AtlasGenConfig,
     layer: MapDataLayer,
@@ -322,6 +271,15 @@ fn generate_precipitation(
             }
         }
     }
+    // Dry out lee sides of mountains and continental interiors.
+    apply_rain_shadow(
+        &mut humi_data,
+        topo_data,
+        logics.get_layer(MapDataLayer::Continents),
+        &config.precipitation.rain_shadow,
+        model,
+        world_size,
+    );
     // Apply the influence map if requested.
     if let Some(inf_layer) = layer.get_influence_layer() {
         handle_influence(&mut humi_data, logics, inf_layer, &config.precipitation);
@@ -332,7 +290,7 @@ fn generate_precipitation(
     vec![layer]
 }
 
-fn generate_climate(
+pub fn generate_climate(
     logics: &mut MapLogicData,
     config: &AtlasGenConfig,
     layer: MapDataLayer,
@@ -360,11 +318,98 @@ fn generate_climate(
     vec![layer]
 }
 
-fn generate_resources(
+/// Generate rivers and lakes from final topography and precipitation.
+pub fn generate_rivers(
+    logics: &mut MapLogicData,
+    config: &AtlasGenConfig,
+    layer: MapDataLayer,
+) -> Vec<MapDataLayer> {
+    // Move out layer data.
+    let mut river_data = logics.pop_layer(layer);
+    let cont_data = logics.get_layer(MapDataLayer::Continents);
+    let topo_data = logics.get_layer(MapDataLayer::RealTopography);
+    let prec_data = logics.get_layer(MapDataLayer::Precipitation);
+    let (width, height) = (
+        config.general.world_size[0] as usize,
+        config.general.world_size[1] as usize,
+    );
+    river_data.fill(0);
+    let model = config.general.generation_model;
+    let edges = matches!(model, WorldModel::Flat);
+    // Fill depressions with a priority flood, starting from the sea and map edges (if flat).
+    // Every tile drains into the tile it was reached from, so water never gets stuck.
+    let mut filled = topo_data.to_vec();
+    let mut receivers = vec![None; filled.len()];
+    let mut visited = vec![false; filled.len()];
+    let mut order = Vec::with_capacity(filled.len());
+    let mut queue = BinaryHeap::new();
+    // Break ties in insertion order, so that water crosses flat areas in straight lines.
+    let mut counter = 0usize;
+    for i in 0..filled.len() {
+        let (x, y) = (i % width, i / width);
+        let edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+        if is_sea(cont_data[i]) || (edges && edge) {
+            visited[i] = true;
+            queue.push(Reverse((filled[i], counter, i)));
+            counter += 1;
+        }
+    }
+    // A globe with no sea still needs an outlet, so use the lowest tile.
+    if queue.is_empty() {
+        if let Some(i) = (0..filled.len()).min_by_key(|i| filled[*i]) {
+            visited[i] = true;
+            queue.push(Reverse((filled[i], counter, i)));
+            counter += 1;
+        }
+    }
+    while let Some(Reverse((value, _, i))) = queue.pop() {
+        order.push(i);
+        for offset in NEIGHBOURS_8 {
+            let Some(j) = config.get_offset_tile(i as u32, offset) else {
+                continue;
+            };
+            let j = j as usize;
+            if visited[j] {
+                continue;
+            }
+            visited[j] = true;
+            filled[j] = filled[j].max(value);
+            receivers[j] = Some(i);
+            queue.push(Reverse((filled[j], counter, j)));
+            counter += 1;
+        }
+    }
+    // Accumulate precipitation downstream, starting from the most upstream tiles.
+    let mut discharge: Vec<f32> = prec_data.iter().map(|x| *x as f32 / 255.0).collect();
+    for &i in order.iter().rev() {
+        if let Some(j) = receivers[i] {
+            discharge[j] += discharge[i];
+        }
+    }
+    // Mark lakes and rivers on land.
+    let threshold = config.rivers.discharge_threshold;
+    for i in 0..river_data.len() {
+        if is_sea(cont_data[i]) {
+            continue;
+        }
+        if config.rivers.lakes && filled[i] - topo_data[i] >= config.rivers.min_lake_depth {
+            river_data[i] = RIVER_LAKE;
+        } else if discharge[i] >= threshold {
+            // Scale logarithmically, so that big rivers don't dwarf the small ones.
+            let value = 1.0 + (discharge[i] / threshold).log2() * RIVER_LOG_SCALE;
+            river_data[i] = value.min((RIVER_LAKE - 1) as f32) as u8;
+        }
+    }
+    // Set new layer data.
+    logics.put_layer(layer, river_data);
+    // This layer should be refreshed.
+    vec![layer]
+}
+
+pub fn generate_resources(
     logics: &mut MapLogicData,
     config: &mut AtlasGenConfig,
     _layer: MapDataLayer,
-    rng: &mut GlobalEntropy<WyRand>,
 ) -> Vec<MapDataLayer> {
     // Get layer data.
     let cont_data = logics.get_layer(MapDataLayer::Continents);
@@ -377,10 +422,16 @@ fn generate_resources(
     let chunk_count = cwidth * cheight;
     let size = config.deposits.chunk_size as usize;
     let mut chunks = vec![DepositChunk::default(); chunk_count];
+    // Every chunk has its own RNG, so that chunks are only rerolled when their own tiles change.
+    let seed = config.deposits.seed as u64;
+    let mut rngs: Vec<_> = (0..chunk_count)
+        .map(|j| WyRand::seed_from_u64(seed << 32 | ((j / cwidth) as u64) << 16 | (j % cwidth) as u64))
+        .collect();
     for i in 0..(width * height) {
         // Find the chunk for this tile.
         let j = ((i / width) / size) * cwidth + (i % width) / size;
         let chunk = &mut chunks[j];
+        let rng = &mut rngs[j];
         if !is_sea(cont_data[i]) {
             chunk.tile_count += 1;
         }
@@ -428,27 +479,27 @@ fn generate_resources(
 }
 
 /// Generate FINAL topography data.
-fn generate_utility_real_topo(logics: &mut MapLogicData) -> Vec<MapDataLayer> {
-    // Move out layer data.
-    let mut real_data = logics.pop_layer(MapDataLayer::RealTopography);
-    let topo_data = logics.get_layer(MapDataLayer::Topography);
+pub fn generate_utility_real_topo(logics: &mut MapLogicData) -> Vec<MapDataLayer> {
+    // Copy out layer data with high precision.
+    let topo_data = logics.get_layer_precise(MapDataLayer::Topography);
+    let mut real_data = topo_data.clone();
     let filter_data = logics.get_layer(MapDataLayer::TopographyFilter);
     // Little trick: topography filter is basically an influence layer.
     apply_influence_from_src(
         &mut real_data,
-        topo_data,
+        &topo_data,
         filter_data,
         InfluenceMode::ScaleDown,
         1.0,
     );
     // Set new layer data.
-    logics.put_layer(MapDataLayer::RealTopography, real_data);
+    logics.put_layer_precise(MapDataLayer::RealTopography, real_data);
     // This layer should be refreshed.
     vec![MapDataLayer::RealTopography]
 }
 
 /// Generate beach smoothing topography filter.
-fn generate_utility_topo_filter(logics: &mut MapLogicData, config: &AtlasGenConfig) -> Vec<MapDataLayer> {
+pub fn generate_utility_topo_filter(logics: &mut MapLogicData, config: &AtlasGenConfig) -> Vec<MapDataLayer> {
     let mut filter_data = logics.pop_layer(MapDataLayer::TopographyFilter);
     filter_data.fill(0);
     // Fetch kernel size and world dimensions.
@@ -472,32 +523,25 @@ fn generate_utility_topo_filter(logics: &mut MapLogicData, config: &AtlasGenConf
         return vec![];
     }
     // Simple NxN blur.
-    match &config.general.generation_model {
-        WorldModel::Flat => {
-            let multiplier = (255 / ((kernel * 2 + 1).pow(2) - 1)) as u16;
-            for y in 0..height {
-                for x in 0..width {
-                    let i = (y * width + x) as usize;
-                    if !is_sea(cont_data[i]) {
-                        let mut value = 0;
-                        for v in -kernel..=kernel {
-                            for u in -kernel..=kernel {
-                                if ((y + v) >= height) || ((x + u) >= width) || ((y + v) < 0) || ((x + u) < 0)
-                                {
-                                    continue;
-                                }
-                                let j = ((y + v) * width + (x + u)) as usize;
-                                if is_sea(cont_data[j]) {
-                                    value += 1;
-                                }
-                            }
+    let multiplier = (255 / ((kernel * 2 + 1).pow(2) - 1)) as u16;
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) as usize;
+            if !is_sea(cont_data[i]) {
+                let mut value = 0;
+                for v in -kernel..=kernel {
+                    for u in -kernel..=kernel {
+                        let Some(j) = config.get_offset_tile(i as u32, (u, v)) else {
+                            continue;
+                        };
+                        if is_sea(cont_data[j as usize]) {
+                            value += 1;
                         }
-                        filter_data[i] = 255 - (value * multiplier * 2).min(255) as u8;
-                    };
+                    }
                 }
-            }
+                filter_data[i] = 255 - (value * multiplier * 2).min(255) as u8;
+            };
         }
-        WorldModel::Globe => todo!(),
     }
     // Set new layer data.
     logics.put_layer(MapDataLayer::TopographyFilter, filter_data);
@@ -505,35 +549,69 @@ fn generate_utility_topo_filter(logics: &mut MapLogicData, config: &AtlasGenConf
     vec![MapDataLayer::TopographyFilter]
 }
 
-/// Generate an influence map for a layer with a given influence shape.
-fn generate_influence(
+/// Generate an influence map for a layer with given influence shapes.
+pub fn generate_influence(
     logics: &mut MapLogicData,
-    shape: impl AsRef<InfluenceShape>,
+    config: impl AsRef<InfluenceConfig>,
     model: WorldModel,
     world_size: [u32; 2],
     layer: MapDataLayer,
 ) -> Vec<MapDataLayer> {
-    let map_data = logics.get_layer_mut(layer);
-    fill_influence(map_data, shape.as_ref(), model, world_size);
+    let mut map_data = logics.pop_layer(layer);
+    fill_influence(
+        &mut map_data,
+        logics.get_image(layer),
+        config.as_ref(),
+        model,
+        world_size,
+    );
+    logics.put_layer(layer, map_data);
+    vec![layer]
+}
+
+/// Generate a user-defined custom layer from its noise algorithm and influence shapes.
+pub fn generate_custom(
+    logics: &mut MapLogicData,
+    config: &AtlasGenConfig,
+    index: usize,
+) -> Vec<MapDataLayer> {
+    let layer = MapDataLayer::Custom(index as u8);
+    let Some(custom) = config.custom.get_layers().get(index) else {
+        return vec![];
+    };
+    // Move out layer data.
+    let mut data = logics.pop_layer(layer);
+    // Get relevant config info.
+    let model = config.general.generation_model;
+    let world_size = config.general.world_size;
+    // Run the noise algorithm (or restore loaded image data, so influence doesn't compound).
+    let image = logics.get_image(layer);
+    match (&custom.algorithm, image) {
+        (NoiseAlgorithm::FromImage, Some(image)) => data.copy_from_slice(image),
+        _ => fill_with_algorithm(&mut data, model, world_size, custom),
+    }
+    // Custom layers have no influence layer, so the influence map is only generated temporarily.
+    if custom.influence.is_used() {
+        let mut influence = vec![0u8; data.len()];
+        fill_influence(&mut influence, image, &custom.influence, model, world_size);
+        apply_influence(&mut data, &influence, InfluenceMode::ScaleUpDown, 1.0);
+    }
+    // Set new layer data.
+    logics.put_layer(layer, data);
+    // This layer should be refreshed.
     vec![layer]
 }
 
 /// Check if influence map should be applied and apply it.
 fn handle_influence(
-    data: &mut [u8],
+    data: &mut [impl LayerValue],
     logics: &mut MapLogicData,
     layer: MapDataLayer,
-    config: impl AsRef<InfluenceShape>,
+    config: impl AsRef<InfluenceConfig>,
 ) {
-    let (use_influence, influence_strength, influence_mode) = match config.as_ref() {
-        InfluenceShape::None => (false, 0.0, Default::default()),
-        InfluenceShape::Circle(x) => (true, x.influence_strength, x.influence_mode),
-        InfluenceShape::Strip(x) => (true, x.influence_strength, x.influence_mode),
-        InfluenceShape::Fbm(x) => (true, x.influence_strength, x.influence_mode),
-        InfluenceShape::FromImage(x) => (true, x.influence_strength, x.influence_mode),
-    };
-    if use_influence {
+    if config.as_ref().is_used() {
         let map_data = logics.get_layer(layer);
-        apply_influence(data, map_data, influence_mode, influence_strength);
+        // All shapes are already combined into one map with scale up/down values.
+        apply_influence(data, map_data, InfluenceMode::ScaleUpDown, 1.0);
     }
 }
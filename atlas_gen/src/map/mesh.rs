@@ -0,0 +1,232 @@
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use atlas_lib::{
+    bevy::math::Vec3,
+    config::{
+        climate::ALTITUDE_STEP, gen::AtlasGenConfig, save_image, save_json, AtlasConfig, Error, WorldModel,
+    },
+    domain::{graphics::MapLogicData, map::MapDataLayer},
+    serde_json::json,
+};
+
+/// Length of the equator in metres, used to scale altitude to tile units.
+const EQUATOR_LENGTH: f32 = 40_075_000.0;
+/// Common name of all exported mesh files.
+const MESH_NAME: &str = "terrain";
+
+/// glTF accessor component type: float.
+const GLTF_FLOAT: u32 = 5126;
+/// glTF accessor component type: unsigned int.
+const GLTF_UNSIGNED_INT: u32 = 5125;
+/// glTF buffer view target: vertex attributes.
+const GLTF_ARRAY_BUFFER: u32 = 34962;
+/// glTF buffer view target: vertex indices.
+const GLTF_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Triangulated terrain, in tile units with Y axis pointing up.
+struct TerrainMesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl TerrainMesh {
+    /// Triangulate final topography as a plane (flat model) or a displaced sphere (globe model).
+    fn new(logics: &MapLogicData, config: &AtlasGenConfig) -> Self {
+        let (width, height) = config.get_world_size();
+        let mesh_config = &config.general.mesh_export;
+        let step = mesh_config.decimation.max(1);
+        let (cols, rows) = (width.div_ceil(step), height.div_ceil(step));
+        let data = logics.get_layer_precise(MapDataLayer::RealTopography);
+        // Altitude in tile units, as if the world was as big as the Earth.
+        let scale = ALTITUDE_STEP / (EQUATOR_LENGTH / width as f32) * mesh_config.exaggeration;
+        let radius = width as f32 / TAU;
+        let mut positions = vec![];
+        let mut uvs = vec![];
+        for j in 0..=rows {
+            for i in 0..=cols {
+                let (u, v) = (i as f32 / cols as f32, j as f32 / rows as f32);
+                // Use the nearest tile.
+                let x = ((u * width as f32) as u32).min(width - 1);
+                let y = ((v * height as f32) as u32).min(height - 1);
+                let altitude = data[(y * width + x) as usize] * scale;
+                let position = match config.get_world_model() {
+                    WorldModel::Flat => {
+                        Vec3::new((u - 0.5) * width as f32, altitude, (v - 0.5) * height as f32)
+                    }
+                    WorldModel::Globe => {
+                        let (lon, lat) = (u * TAU - PI, FRAC_PI_2 - v * PI);
+                        let normal = Vec3::new(lat.cos() * lon.cos(), lat.sin(), -lat.cos() * lon.sin());
+                        normal * (radius + altitude)
+                    }
+                };
+                positions.push(position);
+                uvs.push([u, v]);
+            }
+        }
+        // Two counter-clockwise triangles per grid quad.
+        let mut indices = vec![];
+        for j in 0..rows {
+            for i in 0..cols {
+                let a = j * (cols + 1) + i;
+                let (b, c) = (a + 1, a + cols + 1);
+                let d = c + 1;
+                indices.extend([a, c, d, a, d, b]);
+            }
+        }
+        // Smooth normals, weighted by triangle area.
+        let mut normals = vec![Vec3::ZERO; positions.len()];
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|x| triangle[x] as usize);
+            let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+            for k in [a, b, c] {
+                normals[k] += normal;
+            }
+        }
+        let normals = normals.into_iter().map(|x| x.normalize_or_zero()).collect();
+        Self {
+            positions,
+            normals,
+            uvs,
+            indices,
+        }
+    }
+
+    /// Save as a Wavefront OBJ file, with a material file using the texture.
+    fn save_obj(&self, base_path: &Path, texture: &str) -> Result<(), Error> {
+        let material = format!("{}.mtl", MESH_NAME);
+        let text = format!("newmtl {}\nKa 1 1 1\nKd 1 1 1\nmap_Kd {}\n", MESH_NAME, texture);
+        fs::write(base_path.join(&material), text)?;
+        let path = base_path.join(format!("{}.obj", MESH_NAME));
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "mtllib {}", material)?;
+        writeln!(writer, "o {}", MESH_NAME)?;
+        for p in &self.positions {
+            writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        // OBJ texture coordinates start at the bottom.
+        for [u, v] in &self.uvs {
+            writeln!(writer, "vt {} {}", u, 1.0 - v)?;
+        }
+        for n in &self.normals {
+            writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+        writeln!(writer, "usemtl {}", MESH_NAME)?;
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|x| triangle[x] + 1);
+            writeln!(writer, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Save as a glTF 2.0 file with a separate binary buffer, with a material using the texture.
+    fn save_gltf(&self, base_path: &Path, texture: &str) -> Result<(), Error> {
+        // Buffer layout: positions, normals, texture coordinates, indices.
+        let mut buffer: Vec<u8> = vec![];
+        let mut views = vec![];
+        let mut add_view = |bytes: Vec<u8>, target: u32| {
+            views.push(json!({
+                "buffer": 0,
+                "byteOffset": buffer.len(),
+                "byteLength": bytes.len(),
+                "target": target,
+            }));
+            buffer.extend(bytes);
+        };
+        let floats = |x: &[f32]| x.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+        add_view(
+            floats(
+                &self
+                    .positions
+                    .iter()
+                    .flat_map(|x| x.to_array())
+                    .collect::<Vec<_>>(),
+            ),
+            GLTF_ARRAY_BUFFER,
+        );
+        add_view(
+            floats(&self.normals.iter().flat_map(|x| x.to_array()).collect::<Vec<_>>()),
+            GLTF_ARRAY_BUFFER,
+        );
+        add_view(
+            floats(&self.uvs.iter().flatten().copied().collect::<Vec<_>>()),
+            GLTF_ARRAY_BUFFER,
+        );
+        add_view(
+            self.indices.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            GLTF_ELEMENT_ARRAY_BUFFER,
+        );
+        let binary = format!("{}.bin", MESH_NAME);
+        fs::write(base_path.join(&binary), &buffer)?;
+        // Position accessor requires bounds.
+        let min = self.positions.iter().fold(Vec3::MAX, |acc, x| acc.min(*x));
+        let max = self.positions.iter().fold(Vec3::MIN, |acc, x| acc.max(*x));
+        let count = self.positions.len();
+        let gltf = json!({
+            "asset": { "version": "2.0", "generator": "Atlas Map Generator" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{ "mesh": 0, "name": MESH_NAME }],
+            "meshes": [{
+                "name": MESH_NAME,
+                "primitives": [{
+                    "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 },
+                    "indices": 3,
+                    "material": 0,
+                }],
+            }],
+            "materials": [{
+                "name": MESH_NAME,
+                "pbrMetallicRoughness": {
+                    "baseColorTexture": { "index": 0 },
+                    "metallicFactor": 0.0,
+                    "roughnessFactor": 1.0,
+                },
+            }],
+            "textures": [{ "source": 0 }],
+            "images": [{ "uri": texture }],
+            "buffers": [{ "uri": binary, "byteLength": buffer.len() }],
+            "bufferViews": views,
+            "accessors": [
+                {
+                    "bufferView": 0,
+                    "componentType": GLTF_FLOAT,
+                    "count": count,
+                    "type": "VEC3",
+                    "min": min.to_array(),
+                    "max": max.to_array(),
+                },
+                { "bufferView": 1, "componentType": GLTF_FLOAT, "count": count, "type": "VEC3" },
+                { "bufferView": 2, "componentType": GLTF_FLOAT, "count": count, "type": "VEC2" },
+                {
+                    "bufferView": 3,
+                    "componentType": GLTF_UNSIGNED_INT,
+                    "count": self.indices.len(),
+                    "type": "SCALAR",
+                },
+            ],
+        });
+        save_json(&gltf, base_path.join(format!("{}.gltf", MESH_NAME)))
+    }
+}
+
+/// Helper function
+///
+/// Write final topography as glTF and OBJ terrain meshes, textured with the preview layer, to a directory.
+pub fn export_mesh(base_path: &Path, logics: &MapLogicData, config: &AtlasGenConfig) -> Result<(), Error> {
+    let (width, height) = config.get_world_size();
+    // Bake the preview as the texture.
+    let texture = format!("{}.png", MESH_NAME);
+    let preview = logics.get_layer(MapDataLayer::Preview);
+    save_image(base_path.join(&texture), preview, width, height)?;
+    let mesh = TerrainMesh::new(logics, config);
+    mesh.save_obj(base_path, &texture)?;
+    mesh.save_gltf(base_path, &texture)
+}
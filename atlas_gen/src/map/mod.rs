@@ -1,11 +1,19 @@
+mod erosion;
 mod events;
 mod generation;
+mod mesh;
+mod oceans;
+mod pipeline;
 mod samplers;
+mod winds;
 
 use atlas_lib::{base::map::MapPluginBase, bevy::prelude::*, config::gen::AtlasGenConfig};
 
 use crate::map::events::*;
 
+pub use events::export_world;
+pub use pipeline::generate_all;
+
 /// Plugin responsible for the world graphics and generation.
 pub struct MapPlugin;
 
@@ -16,13 +24,17 @@ fn build(&self, app: &mut App) {
                 Update,
                 (
                     update_event_loaded.run_if(check_event_loaded),
+                    update_event_loaded_precise.run_if(check_event_loaded_precise),
                     update_event_saved.run_if(check_event_saved),
                     update_event_rendered.run_if(check_event_rendered),
                     update_event_clear.run_if(check_event_clear),
                     update_event_generate.run_if(check_event_generate),
+                    update_event_brush.run_if(check_event_brush),
                     update_event_climatemap.run_if(check_event_climatemap),
                     update_event_import.run_if(check_event_import),
                     update_event_export.run_if(check_event_export),
+                    update_event_export_gis.run_if(check_event_export_gis),
+                    update_event_export_mesh.run_if(check_event_export_mesh),
                 ),
             );
     }
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+
+use atlas_lib::{
+    bevy::utils::petgraph::matrix_graph::Zero,
+    bevy_egui::egui::lerp,
+    config::{
+        climate::{celsius_clamp, celsius_from_byte, celsius_to_byte},
+        gen::{ContinentalityConfig, OceanCurrentsConfig, WindBandsConfig},
+        WorldModel, NEIGHBOURS_8,
+    },
+    domain::map::is_sea,
+};
+
+use crate::map::winds::row_latitude;
+
+/// Shift temperature of land tiles based on their distance to the sea.
+pub fn apply_continentality(
+    data: &mut [u8],
+    cont_data: &[u8],
+    config: &ContinentalityConfig,
+    model: WorldModel,
+    world_size: [u32; 2],
+) {
+    if config.range == 0 {
+        return;
+    }
+    let (width, height) = (world_size[0] as usize, world_size[1] as usize);
+    // Find distance to the closest sea tile, up to the range.
+    let mut distance = vec![u32::MAX; data.len()];
+    let mut queue = VecDeque::new();
+    for i in 0..data.len() {
+        if is_sea(cont_data[i]) {
+            distance[i] = 0;
+            queue.push_back(i);
+        }
+    }
+    while let Some(i) = queue.pop_front() {
+        if distance[i] >= config.range {
+            continue;
+        }
+        for offset in NEIGHBOURS_8 {
+            let Some(j) = model.offset_tile((world_size[0], world_size[1]), i as u32, offset) else {
+                continue;
+            };
+            let j = j as usize;
+            if distance[j] == u32::MAX {
+                distance[j] = distance[i] + 1;
+                queue.push_back(j);
+            }
+        }
+    }
+    // Interiors get the full shift, which depends on latitude.
+    for i in 0..data.len() {
+        if is_sea(cont_data[i]) {
+            continue;
+        }
+        let inland = distance[i].min(config.range) as f32 / config.range as f32;
+        let latitude = row_latitude(i / width, height).abs() / 90.0;
+        let shift = lerp(config.equator_shift..=config.pole_shift, latitude) * inland;
+        shift_temperature(&mut data[i], shift);
+    }
+}
+
+/// Shift temperature of coastal land tiles by warm and cold ocean currents.
+/// Onshore winds push warm currents to the coast, while offshore winds cause cold upwelling,
+/// so that west and east coasts at the same latitude differ.
+pub fn apply_ocean_currents(
+    data: &mut [u8],
+    cont_data: &[u8],
+    config: &OceanCurrentsConfig,
+    winds: &WindBandsConfig,
+    model: WorldModel,
+    world_size: [u32; 2],
+) {
+    if config.range == 0 {
+        return;
+    }
+    let (width, height) = (world_size[0] as usize, world_size[1] as usize);
+    let range = config.range as usize;
+    let falloff = |distance: Option<usize>| match distance {
+        Some(d) if d <= range => (range + 1 - d) as f32 / range as f32,
+        _ => 0.0,
+    };
+    for y in 0..height {
+        let wind = winds.wind_at(row_latitude(y, height));
+        if wind.is_zero() {
+            continue;
+        }
+        let row = &cont_data[y * width..(y + 1) * width];
+        // Distance to the closest sea tile to the west and to the east.
+        let west = coast_distances(cont_data, model, world_size, y, 1);
+        let east = coast_distances(cont_data, model, world_size, y, -1);
+        // Sea in the upwind direction means onshore winds.
+        let (onshore, offshore) = if wind > 0.0 { (west, east) } else { (east, west) };
+        for x in 0..width {
+            if is_sea(row[x]) {
+                continue;
+            }
+            let shift = config.warm_shift * falloff(onshore[x]) - config.cold_shift * falloff(offshore[x]);
+            shift_temperature(&mut data[y * width + x], shift * wind.abs());
+        }
+    }
+}
+
+/// Helper function
+///
+/// Count tiles since the last sea tile for every tile in a row, going east (1) or west (-1).
+fn coast_distances(
+    cont_data: &[u8],
+    model: WorldModel,
+    world_size: [u32; 2],
+    y: usize,
+    direction: i32,
+) -> Vec<Option<usize>> {
+    let width = world_size[0] as usize;
+    let start = if direction > 0 {
+        y * width
+    } else {
+        y * width + width - 1
+    };
+    // Globes wrap around east-west, so go around twice to carry distances over the edge.
+    let laps = match model {
+        WorldModel::Flat => 1,
+        WorldModel::Globe => 2,
+    };
+    let mut result = vec![None; width];
+    let mut distance = None;
+    let mut next = Some(start as u32);
+    for _ in 0..width * laps {
+        let Some(i) = next else {
+            break;
+        };
+        distance = if is_sea(cont_data[i as usize]) {
+            Some(0)
+        } else {
+            distance.map(|d| d + 1)
+        };
+        result[i as usize % width] = distance;
+        next = model.offset_tile((world_size[0], world_size[1]), i, (direction, 0));
+    }
+    result
+}
+
+/// Helper function
+///
+/// Add a temperature difference (in degrees Celsius) to a tile.
+fn shift_temperature(value: &mut u8, shift: f32) {
+    *value = celsius_to_byte(celsius_clamp(celsius_from_byte(*value) + shift));
+}
@@ -0,0 +1,393 @@
+use std::collections::HashSet;
+
+use atlas_lib::{
+    config::gen::{AtlasGenConfig, NoiseAlgorithm},
+    domain::{graphics::MapLogicData, map::MapDataLayer},
+};
+
+use crate::map::generation::{
+    generate_climate, generate_continents, generate_custom, generate_influence, generate_precipitation,
+    generate_preview, generate_resources, generate_rivers, generate_temperature, generate_topography,
+    generate_utility_real_topo, generate_utility_topo_filter,
+};
+
+/// A single step of the generation pipeline, producing one layer from other layers.
+struct GenerationStep {
+    /// Layer produced by this step.
+    output: MapDataLayer,
+    /// Layers read by this step. Some dependencies only exist with certain config.
+    inputs: fn(&AtlasGenConfig) -> Vec<MapDataLayer>,
+    /// Influence layer applied by this step. It's generated first, but changing it doesn't
+    /// regenerate this step, so that painted or loaded layer data isn't overwritten.
+    influence: Option<MapDataLayer>,
+    /// Generation procedure. Returns layers that should be refreshed.
+    run: fn(&mut MapLogicData, &mut AtlasGenConfig) -> Vec<MapDataLayer>,
+}
+
+/// All generation steps. Order doesn't matter, steps are sorted by their dependencies.
+static GENERATION_STEPS: [GenerationStep; 14] = [
+    // Influence
+    GenerationStep {
+        output: MapDataLayer::ContinentsInfluence,
+        inputs: |_| vec![],
+        influence: None,
+        run: |logics, config| {
+            let (model, world_size) = (config.general.generation_model, config.general.world_size);
+            let layer = MapDataLayer::ContinentsInfluence;
+            generate_influence(logics, &config.continents, model, world_size, layer)
+        },
+    },
+    GenerationStep {
+        output: MapDataLayer::TopographyInfluence,
+        inputs: |_| vec![],
+        influence: None,
+        run: |logics, config| {
+            let (model, world_size) = (config.general.generation_model, config.general.world_size);
+            let layer = MapDataLayer::TopographyInfluence;
+            generate_influence(logics, &config.topography, model, world_size, layer)
+        },
+    },
+    GenerationStep {
+        output: MapDataLayer::TemperatureInfluence,
+        inputs: |_| vec![],
+        influence: None,
+        run: |logics, config| {
+            let (model, world_size) = (config.general.generation_model, config.general.world_size);
+            let layer = MapDataLayer::TemperatureInfluence;
+            generate_influence(logics, &config.temperature, model, world_size, layer)
+        },
+    },
+    GenerationStep {
+        output: MapDataLayer::PrecipitationInfluence,
+        inputs: |_| vec![],
+        influence: None,
+        run: |logics, config| {
+            let (model, world_size) = (config.general.generation_model, config.general.world_size);
+            let layer = MapDataLayer::PrecipitationInfluence;
+            generate_influence(logics, &config.precipitation, model, world_size, layer)
+        },
+    },
+    // Base layers
+    GenerationStep {
+        output: MapDataLayer::Continents,
+        inputs: |_| vec![],
+        influence: Some(MapDataLayer::ContinentsInfluence),
+        run: |logics, config| generate_continents(logics, config, MapDataLayer::Continents),
+    },
+    GenerationStep {
+        output: MapDataLayer::Topography,
+        inputs: |config| {
+            // Tectonic continents also shape the topography.
+            match config.continents.algorithm {
+                NoiseAlgorithm::Tectonic(_) => vec![MapDataLayer::Continents],
+                _ => vec![],
+            }
+        },
+        influence: Some(MapDataLayer::TopographyInfluence),
+        run: |logics, config| generate_topography(logics, config, MapDataLayer::Topography),
+    },
+    // Derived layers
+    GenerationStep {
+        output: MapDataLayer::TopographyFilter,
+        inputs: |_| vec![MapDataLayer::Continents],
+        influence: None,
+        run: |logics, config| generate_utility_topo_filter(logics, config),
+    },
+    GenerationStep {
+        output: MapDataLayer::RealTopography,
+        inputs: |_| vec![MapDataLayer::Topography, MapDataLayer::TopographyFilter],
+        influence: None,
+        run: |logics, _| generate_utility_real_topo(logics),
+    },
+    GenerationStep {
+        output: MapDataLayer::Temperature,
+        inputs: |_| vec![MapDataLayer::Continents, MapDataLayer::RealTopography],
+        influence: Some(MapDataLayer::TemperatureInfluence),
+        run: |logics, config| generate_temperature(logics, config, MapDataLayer::Temperature),
+    },
+    GenerationStep {
+        output: MapDataLayer::Precipitation,
+        inputs: |_| vec![MapDataLayer::Continents, MapDataLayer::RealTopography],
+        influence: Some(MapDataLayer::PrecipitationInfluence),
+        run: |logics, config| generate_precipitation(logics, config, MapDataLayer::Precipitation),
+    },
+    GenerationStep {
+        output: MapDataLayer::Rivers,
+        inputs: |_| {
+            vec![
+                MapDataLayer::Continents,
+                MapDataLayer::RealTopography,
+                MapDataLayer::Precipitation,
+            ]
+        },
+        influence: None,
+        run: |logics, config| generate_rivers(logics, config, MapDataLayer::Rivers),
+    },
+    GenerationStep {
+        output: MapDataLayer::Climate,
+        inputs: |_| {
+            vec![
+                MapDataLayer::Continents,
+                MapDataLayer::Temperature,
+                MapDataLayer::Precipitation,
+            ]
+        },
+        influence: None,
+        run: |logics, config| generate_climate(logics, config, MapDataLayer::Climate),
+    },
+    GenerationStep {
+        output: MapDataLayer::Deposits,
+        inputs: |_| vec![MapDataLayer::Continents, MapDataLayer::Climate],
+        influence: None,
+        run: |logics, config| generate_resources(logics, config, MapDataLayer::Deposits),
+    },
+    GenerationStep {
+        output: MapDataLayer::Preview,
+        inputs: |_| {
+            vec![
+                MapDataLayer::Continents,
+                MapDataLayer::RealTopography,
+                MapDataLayer::Rivers,
+                MapDataLayer::Climate,
+            ]
+        },
+        influence: None,
+        run: |logics, config| generate_preview(logics, config),
+    },
+];
+
+/// Choose relevant generation procedure based on layer.
+pub fn generate(
+    layer: MapDataLayer,
+    logics: &mut MapLogicData,
+    config: &mut AtlasGenConfig,
+) -> Vec<MapDataLayer> {
+    // Custom layers don't depend on other layers, and nothing depends on them.
+    if let MapDataLayer::Custom(i) = layer {
+        return generate_custom(logics, config, i as usize);
+    }
+    let step = GENERATION_STEPS
+        .iter()
+        .find(|x| x.output == layer)
+        .expect("Every layer should have a generation step");
+    (step.run)(logics, config)
+}
+
+/// Refresh other layers (if needed) after modifying this layer.
+pub fn after_generate(
+    layer: MapDataLayer,
+    logics: &mut MapLogicData,
+    config: &mut AtlasGenConfig,
+) -> Vec<MapDataLayer> {
+    run_dirty_steps(&[layer], logics, config)
+}
+
+/// Generate all layers from scratch, in dependency order, followed by custom layers.
+pub fn generate_all(logics: &mut MapLogicData, config: &mut AtlasGenConfig) {
+    for step in sort_steps(config) {
+        (step.run)(logics, config);
+    }
+    for i in 0..config.custom.get_layers().len() {
+        generate_custom(logics, config, i);
+    }
+}
+
+/// Helper function
+///
+/// Run every step that (directly or indirectly) depends on changed layers, in dependency order.
+/// Returns layers that should be refreshed.
+fn run_dirty_steps(
+    changed: &[MapDataLayer],
+    logics: &mut MapLogicData,
+    config: &mut AtlasGenConfig,
+) -> Vec<MapDataLayer> {
+    let mut regen_layers = vec![];
+    for step in dirty_steps(changed, config) {
+        regen_layers.extend((step.run)(logics, config));
+    }
+    regen_layers
+}
+
+/// Helper function
+///
+/// Find every step that (directly or indirectly) depends on changed layers, in dependency order.
+/// Influence layers are not followed, see [`GenerationStep::influence`].
+fn dirty_steps(changed: &[MapDataLayer], config: &AtlasGenConfig) -> Vec<&'static GenerationStep> {
+    let mut dirty: HashSet<MapDataLayer> = changed.iter().copied().collect();
+    let mut steps = vec![];
+    for step in sort_steps(config) {
+        if changed.contains(&step.output) || !(step.inputs)(config).iter().any(|x| dirty.contains(x)) {
+            continue;
+        }
+        dirty.insert(step.output);
+        steps.push(step);
+    }
+    steps
+}
+
+/// Helper function
+///
+/// Sort generation steps topologically, so that every step comes after steps producing its inputs
+/// (and its influence layer).
+fn sort_steps(config: &AtlasGenConfig) -> Vec<&'static GenerationStep> {
+    let mut remaining: Vec<&GenerationStep> = GENERATION_STEPS.iter().collect();
+    let mut done: HashSet<MapDataLayer> = HashSet::new();
+    let mut sorted = vec![];
+    while !remaining.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|x| {
+            let influence_done = x.influence.map_or(true, |y| done.contains(&y));
+            influence_done && (x.inputs)(config).iter().all(|y| done.contains(y))
+        });
+        assert!(
+            !ready.is_empty(),
+            "Generation steps should not have cyclic dependencies"
+        );
+        done.extend(ready.iter().map(|x| x.output));
+        sorted.extend(ready);
+        remaining = rest;
+    }
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use atlas_lib::config::gen::TectonicConfig;
+
+    use super::*;
+
+    use MapDataLayer::*;
+
+    /// Layers regenerated after changing a layer, in the same order as `sort_steps`.
+    fn dirty_layers(layer: MapDataLayer, config: &AtlasGenConfig) -> Vec<MapDataLayer> {
+        dirty_steps(&[layer], config).iter().map(|x| x.output).collect()
+    }
+
+    fn sorted_layers(config: &AtlasGenConfig) -> Vec<MapDataLayer> {
+        sort_steps(config).iter().map(|x| x.output).collect()
+    }
+
+    fn tectonic_config() -> AtlasGenConfig {
+        let mut config = AtlasGenConfig::default();
+        config.continents.algorithm = NoiseAlgorithm::Tectonic(TectonicConfig::default());
+        config
+    }
+
+    #[test]
+    fn steps_come_after_their_inputs() {
+        for config in [AtlasGenConfig::default(), tectonic_config()] {
+            let sorted = sorted_layers(&config);
+            assert_eq!(sorted.len(), GENERATION_STEPS.len());
+            for step in GENERATION_STEPS.iter() {
+                let position = sorted.iter().position(|x| *x == step.output).unwrap();
+                for input in (step.inputs)(&config).iter().chain(step.influence.iter()) {
+                    let input_position = sorted.iter().position(|x| x == input).unwrap();
+                    assert!(input_position < position, "{:?} before {:?}", input, step.output);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sorted_order() {
+        let influence = [
+            ContinentsInfluence,
+            TopographyInfluence,
+            TemperatureInfluence,
+            PrecipitationInfluence,
+        ];
+        let expected = [
+            vec![Continents, Topography],
+            vec![TopographyFilter],
+            vec![RealTopography],
+            vec![Temperature, Precipitation],
+            vec![Rivers, Climate],
+            vec![Deposits, Preview],
+        ];
+        let sorted = sorted_layers(&AtlasGenConfig::default());
+        assert_eq!(sorted[..4], influence);
+        assert_eq!(sorted[4..], expected.concat());
+        // Tectonic continents shape the topography, so it has to wait for them.
+        let expected = [
+            vec![Continents],
+            vec![Topography, TopographyFilter],
+            vec![RealTopography],
+            vec![Temperature, Precipitation],
+            vec![Rivers, Climate],
+            vec![Deposits, Preview],
+        ];
+        let sorted = sorted_layers(&tectonic_config());
+        assert_eq!(sorted[..4], influence);
+        assert_eq!(sorted[4..], expected.concat());
+    }
+
+    /// Compare with the hand-written cascade this pipeline replaced.
+    #[test]
+    fn dirty_layers_match_old_cascade() {
+        let config = AtlasGenConfig::default();
+        let everything_below_topography = [
+            RealTopography,
+            Temperature,
+            Precipitation,
+            Rivers,
+            Climate,
+            Deposits,
+            Preview,
+        ];
+        let expected = [
+            (
+                Continents,
+                vec![
+                    TopographyFilter,
+                    RealTopography,
+                    Temperature,
+                    Precipitation,
+                    Rivers,
+                    Climate,
+                    Deposits,
+                    Preview,
+                ],
+            ),
+            // The old cascade also regenerated the topography filter, which only depends on continents.
+            (Topography, everything_below_topography.to_vec()),
+            (Temperature, vec![Climate, Deposits, Preview]),
+            (Precipitation, vec![Rivers, Climate, Deposits, Preview]),
+            (Climate, vec![Deposits, Preview]),
+            (Rivers, vec![Preview]),
+            (Preview, vec![]),
+            // The old cascade didn't handle derived layers. They are only changed by loading data.
+            (TopographyFilter, everything_below_topography.to_vec()),
+            (RealTopography, everything_below_topography[1..].to_vec()),
+            // The preview doesn't show deposits, the old cascade refreshed it for every layer.
+            (Deposits, vec![]),
+            // Influence maps are not followed, as in the old cascade (besides refreshing the preview).
+            (ContinentsInfluence, vec![]),
+            (TopographyInfluence, vec![]),
+            (TemperatureInfluence, vec![]),
+            (PrecipitationInfluence, vec![]),
+        ];
+        for (layer, expected) in expected {
+            assert_eq!(
+                dirty_layers(layer, &config),
+                expected,
+                "after changing {:?}",
+                layer
+            );
+        }
+        // Tectonic continents also shape the topography.
+        let config = tectonic_config();
+        assert_eq!(
+            dirty_layers(Continents, &config),
+            [
+                Topography,
+                TopographyFilter,
+                RealTopography,
+                Temperature,
+                Precipitation,
+                Rivers,
+                Climate,
+                Deposits,
+                Preview
+            ]
+        );
+    }
+}
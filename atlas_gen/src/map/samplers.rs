@@ -1,20 +1,90 @@
 use atlas_lib::{
-    bevy::{math::Vec2, utils::petgraph::matrix_graph::Zero},
+    bevy::{
+        math::{Vec2, Vec3},
+        utils::petgraph::matrix_graph::Zero,
+    },
     bevy_egui::egui::lerp,
+    bevy_prng::WyRand,
     config::{
         climate::{celsius_to_fraction, precip_to_fraction},
         gen::{
-            FbmConfig, InfluenceCircleConfig, InfluenceMode, InfluenceShape, InfluenceStripConfig,
-            LatitudinalPrecipitationLerp, LatitudinalTemperatureLerp, NoiseAlgorithm, QuadPointLerp,
+            BillowConfig, DomainWarpConfig, FbmConfig, InfluenceCircleConfig, InfluenceCompositing,
+            InfluenceConfig, InfluenceMode, InfluencePolygonConfig, InfluenceShape, InfluenceSplineConfig,
+            InfluenceStripConfig, LatitudinalPrecipitationLerp, LatitudinalTemperatureLerp, NoiseAlgorithm,
+            QuadPointLerp, RidgedConfig, TectonicConfig, WorleyConfig, WorleyDistance, WorleyReturnType,
         },
         WorldModel,
     },
+    rand::{Rng, SeedableRng},
+};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use noise::{
+    core::worley::{distance_functions, ReturnType},
+    Billow, Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, PerlinSurflet, RidgedMulti, SuperSimplex,
+    Worley,
 };
-use noise::{Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, PerlinSurflet, SuperSimplex};
+
+/// Number of octaves of the domain warping noise.
+const WARP_OCTAVES: usize = 3;
+/// Offset between domain warping noise samples for both axes.
+const WARP_SHIFT: f64 = 100.0;
+/// Base elevation of continental plates.
+const CONTINENTAL_ELEVATION: f32 = 0.6;
+/// Base elevation of oceanic plates.
+const OCEANIC_ELEVATION: f32 = 0.2;
+/// Height of oceanic ridges relative to the depth of continental rifts.
+const RIDGE_RATIO: f32 = 0.5;
+
+/// Tolerance (in radians) for checking whether a point lies on a great circle arc.
+const ARC_EPSILON: f32 = 1e-4;
+/// Number of line segments approximating a spline between two consecutive points.
+const SPLINE_STEPS: usize = 16;
+
+/// Mapping of equirectangular map tiles onto a sphere.
+struct SphereProjection {
+    width: f32,
+    height: f32,
+}
+
+impl SphereProjection {
+    fn new(world_size: [u32; 2]) -> Self {
+        Self {
+            width: world_size[0] as f32,
+            height: world_size[1] as f32,
+        }
+    }
+
+    /// Get a point on the unit sphere for a tile position.
+    fn to_sphere(&self, p: Vec2) -> Vec3 {
+        let lon = (p.x + 0.5) / self.width * TAU - PI;
+        let lat = FRAC_PI_2 - (p.y + 0.5) / self.height * PI;
+        Vec3::new(lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin())
+    }
+
+    /// Get sphere radius measured in tiles along the equator.
+    fn radius(&self) -> f32 {
+        self.width / TAU
+    }
+
+    /// Get a tile position and its copies shifted by world width in both directions.
+    fn wrapped(&self, p: Vec2) -> [Vec2; 3] {
+        [p, p - Vec2::X * self.width, p + Vec2::X * self.width]
+    }
+
+    /// Get great circle distance between two tile positions, measured in tiles along the equator.
+    fn distance(&self, a: Vec2, b: Vec2) -> f32 {
+        self.to_sphere(a).angle_between(self.to_sphere(b)) * self.radius()
+    }
+}
 
 /// A Sampler allows to sample (obtain) a value in [0.0, 1.0] range in 2D space.
 trait Sampler {
     fn sample(&self, p: Vec2) -> f32;
+    /// Sample a tile projected onto a sphere. Uses flat sampling by default.
+    fn sample_globe(&self, p: Vec2, _sphere: &SphereProjection) -> f32 {
+        self.sample(p)
+    }
     fn offset_origin(&mut self, _offset: Vec2) {}
     fn set_scale(&mut self, _scale: f32) {}
 }
@@ -47,6 +117,15 @@ fn sample(&self, p: Vec2) -> f32 {
         self.midpoint.lerp(norm)
     }
 
+    fn sample_globe(&self, p: Vec2, sphere: &SphereProjection) -> f32 {
+        // Calculate the great circle distance from circle center.
+        let len = sphere.distance(p, self.offset);
+        // Transform the distance as a fraction of radius.
+        let norm = (len / self.radius).clamp(0.0, 1.0);
+        // Interpolate value.
+        self.midpoint.lerp(norm)
+    }
+
     fn offset_origin(&mut self, offset: Vec2) {
         self.offset += offset;
     }
@@ -140,51 +219,356 @@ fn sample(&self, p: Vec2) -> f32 {
         self.midpoint.lerp(norm)
     }
 
+    fn sample_globe(&self, p: Vec2, sphere: &SphereProjection) -> f32 {
+        let point = sphere.to_sphere(p);
+        let start = sphere.to_sphere(self.offset + self.start);
+        let end = sphere.to_sphere(self.offset + self.end);
+        // Project point on the great circle going through strip ends and see if it lands between them.
+        let normal = start.cross(end).normalize_or_zero();
+        let projected = (point - normal * point.dot(normal)).normalize_or_zero();
+        let on_arc = normal != Vec3::ZERO
+            && projected != Vec3::ZERO
+            && (start.angle_between(projected) + projected.angle_between(end) - start.angle_between(end))
+                .abs()
+                < ARC_EPSILON;
+        // Otherwise, see how close the point is to strip ends.
+        let angle = if on_arc {
+            point.dot(normal).abs().asin()
+        } else {
+            point.angle_between(start).min(point.angle_between(end))
+        };
+        let norm = (angle * sphere.radius() / self.thickness).min(1.0);
+        // Interpolate value.
+        self.midpoint.lerp(norm)
+    }
+
+    fn offset_origin(&mut self, offset: Vec2) {
+        self.offset += offset;
+    }
+}
+
+/// Sample "closeness" to a polygon. Falloff is centered on polygon edges.
+struct PolygonSampler {
+    offset: Vec2,
+    vertices: Vec<Vec2>,
+    falloff: f32,
+    midpoint: QuadPointLerp,
+}
+
+impl PolygonSampler {
+    fn new(config: &InfluencePolygonConfig) -> Self {
+        let offset = Vec2::new(config.offset[0] as f32, config.offset[1] as f32);
+        let vertices = config
+            .vertices
+            .iter()
+            .map(|x| Vec2::new(x.position[0] as f32, x.position[1] as f32))
+            .collect();
+        Self {
+            offset,
+            vertices,
+            // Avoid division by zero.
+            falloff: (config.falloff as f32).max(1.0),
+            midpoint: config.midpoint.clone_precalc(),
+        }
+    }
+
+    /// Get value falloff for a point: 0.0 deep inside the polygon, 0.5 on its edges, 1.0 far outside.
+    fn norm(&self, p: Vec2) -> f32 {
+        let p = p - self.offset;
+        let mut inside = false;
+        let mut distance = f32::MAX;
+        let next = self.vertices.iter().cycle().skip(1);
+        for (a, b) in self.vertices.iter().zip(next) {
+            distance = distance.min(project_to_segment(p, *a, *b).0);
+            // Even-odd rule: count edges crossed by a ray going right.
+            if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+        }
+        let distance = if inside { -distance } else { distance };
+        ((distance + self.falloff) / (2.0 * self.falloff)).clamp(0.0, 1.0)
+    }
+}
+
+impl Sampler for PolygonSampler {
+    fn sample(&self, p: Vec2) -> f32 {
+        self.midpoint.lerp(self.norm(p))
+    }
+
+    fn sample_globe(&self, p: Vec2, sphere: &SphereProjection) -> f32 {
+        // Keep the flat shape, but wrap it around east-west.
+        let norm = sphere
+            .wrapped(p)
+            .into_iter()
+            .map(|x| self.norm(x))
+            .fold(1.0, f32::min);
+        self.midpoint.lerp(norm)
+    }
+
+    fn offset_origin(&mut self, offset: Vec2) {
+        self.offset += offset;
+    }
+}
+
+/// Sample "closeness" to a curved band going through a list of points, with variable thickness.
+struct SplineSampler {
+    offset: Vec2,
+    /// Points along the curve, with band thickness at each point.
+    curve: Vec<(Vec2, f32)>,
+    midpoint: QuadPointLerp,
+}
+
+impl SplineSampler {
+    fn new(config: &InfluenceSplineConfig) -> Self {
+        let offset = Vec2::new(config.offset[0] as f32, config.offset[1] as f32);
+        let points: Vec<_> = config
+            .points
+            .iter()
+            .map(|x| {
+                (
+                    Vec2::new(x.position[0] as f32, x.position[1] as f32),
+                    // Avoid division by zero.
+                    (x.thickness as f32).max(1.0),
+                )
+            })
+            .collect();
+        // Approximate a Catmull-Rom spline with line segments, duplicating the end points.
+        let mut curve = vec![];
+        let last = points.len().saturating_sub(1);
+        for i in 0..last {
+            let (p0, p1) = (points[i.saturating_sub(1)].0, points[i].0);
+            let (p2, p3) = (points[i + 1].0, points[(i + 2).min(last)].0);
+            for step in 0..SPLINE_STEPS {
+                let t = step as f32 / SPLINE_STEPS as f32;
+                let thickness = points[i].1 + (points[i + 1].1 - points[i].1) * t;
+                curve.push((catmull_rom(p0, p1, p2, p3, t), thickness));
+            }
+        }
+        curve.extend(points.last());
+        Self {
+            offset,
+            curve,
+            midpoint: config.midpoint.clone_precalc(),
+        }
+    }
+
+    /// Get distance to the curve as a fraction of band thickness.
+    fn norm(&self, p: Vec2) -> f32 {
+        let p = p - self.offset;
+        if let [(point, thickness)] = self.curve.as_slice() {
+            return (p.distance(*point) / thickness).min(1.0);
+        }
+        let mut norm = 1f32;
+        for segment in self.curve.windows(2) {
+            let ((a, thickness_a), (b, thickness_b)) = (segment[0], segment[1]);
+            let (distance, t) = project_to_segment(p, a, b);
+            let thickness = thickness_a + (thickness_b - thickness_a) * t;
+            norm = norm.min(distance / thickness);
+        }
+        norm
+    }
+}
+
+impl Sampler for SplineSampler {
+    fn sample(&self, p: Vec2) -> f32 {
+        self.midpoint.lerp(self.norm(p))
+    }
+
+    fn sample_globe(&self, p: Vec2, sphere: &SphereProjection) -> f32 {
+        // Keep the flat shape, but wrap it around east-west.
+        let norm = sphere
+            .wrapped(p)
+            .into_iter()
+            .map(|x| self.norm(x))
+            .fold(1.0, f32::min);
+        self.midpoint.lerp(norm)
+    }
+
     fn offset_origin(&mut self, offset: Vec2) {
         self.offset += offset;
     }
 }
 
-/// Sample Fbm noise value in 2D space.
-struct FbmSampler<N> {
+/// Helper function
+///
+/// Get distance from a point to a line segment, and position of the closest point on the segment (in [0.0, 1.0] range).
+fn project_to_segment(p: Vec2, a: Vec2, b: Vec2) -> (f32, f32) {
+    let ab = b - a;
+    let t = if ab.length_squared().is_zero() {
+        0.0
+    } else {
+        ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+    };
+    (p.distance(a + ab * t), t)
+}
+
+/// Helper function
+///
+/// Get a point on a Catmull-Rom spline segment between `p1` and `p2`.
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Displace sample coordinates with a secondary noise field.
+struct DomainWarp {
+    noise: Fbm<Perlin>,
+    iterations: u8,
+    strength: f32,
+}
+
+impl DomainWarp {
+    fn new(config: &DomainWarpConfig, seed: u32) -> Option<Self> {
+        if config.iterations == 0 || config.strength.is_zero() {
+            return None;
+        }
+        let noise = Fbm::<Perlin>::new(seed.wrapping_add(1))
+            .set_octaves(WARP_OCTAVES)
+            .set_frequency(config.frequency as f64);
+        Some(Self {
+            noise,
+            iterations: config.iterations,
+            strength: config.strength,
+        })
+    }
+
+    fn apply(&self, mut xy: Vec2) -> Vec2 {
+        for _ in 0..self.iterations {
+            // Sample two distant parts of the warp field for both axes.
+            let dx = self.noise.get([xy.x as f64, xy.y as f64]);
+            let dy = self
+                .noise
+                .get([xy.x as f64 + WARP_SHIFT, xy.y as f64 + WARP_SHIFT]);
+            xy += Vec2::new(dx as f32, dy as f32) * self.strength;
+        }
+        xy
+    }
+
+    fn apply_sphere(&self, mut xyz: Vec3) -> Vec3 {
+        for _ in 0..self.iterations {
+            // Sample three distant parts of the warp field for all axes.
+            let p = [xyz.x as f64, xyz.y as f64, xyz.z as f64];
+            let shifted = |shift: f64| [p[0] + shift, p[1] + shift, p[2] + shift];
+            let dx = self.noise.get(p);
+            let dy = self.noise.get(shifted(WARP_SHIFT));
+            let dz = self.noise.get(shifted(WARP_SHIFT * 2.0));
+            xyz += Vec3::new(dx as f32, dy as f32, dz as f32) * self.strength;
+        }
+        xyz
+    }
+}
+
+/// Sample noise value in 2D space.
+struct NoiseSampler<F> {
     origin: Vec2,
     scale: f32,
-    noise: Fbm<N>,
+    noise: F,
+    warp: Option<DomainWarp>,
     bias: f32,
     midpoint: QuadPointLerp,
 }
 
-impl<N> FbmSampler<N>
+impl<F> NoiseSampler<F> {
+    /// Scale a raw noise sample to [0.0, 1.0] range and interpolate it.
+    fn interpolate(&self, sample: f64) -> f32 {
+        let sample = (sample + 1.4) / 2.8;
+        self.midpoint
+            .lerp(((sample as f32).clamp(0.0, 1.0) + self.bias).clamp(0.0, 1.0))
+    }
+
+    fn with_noise(noise: F, offset: [f32; 2], bias: f32, midpoint: &QuadPointLerp) -> Self {
+        Self {
+            origin: Vec2::new(offset[0], offset[1]),
+            scale: 1.0,
+            noise,
+            warp: None,
+            bias,
+            midpoint: midpoint.clone_precalc(),
+        }
+    }
+}
+
+impl<N> NoiseSampler<Fbm<N>>
 where
     N: Default + noise::Seedable,
 {
     pub fn new(config: &FbmConfig) -> Self {
-        let origin = Vec2::new(config.offset[0], config.offset[1]);
         let noise = Fbm::<N>::new(config.seed)
             .set_octaves(config.detail as usize)
             .set_frequency(config.frequency as f64)
             .set_lacunarity(config.neatness as f64)
             .set_persistence(config.roughness as f64);
         Self {
-            origin,
-            scale: 1.0,
-            noise,
-            bias: config.bias,
-            midpoint: config.midpoint.clone_precalc(),
+            warp: DomainWarp::new(&config.warp, config.seed),
+            ..Self::with_noise(noise, config.offset, config.bias, &config.midpoint)
         }
     }
 }
 
-impl<N> Sampler for FbmSampler<N>
+impl NoiseSampler<Worley> {
+    pub fn new_worley(config: &WorleyConfig) -> Self {
+        let noise = Worley::new(config.seed)
+            .set_frequency(config.frequency as f64)
+            .set_return_type(match config.return_type {
+                WorleyReturnType::Distance => ReturnType::Distance,
+                WorleyReturnType::Value => ReturnType::Value,
+            });
+        let noise = match config.distance {
+            WorleyDistance::Euclidean => noise.set_distance_function(distance_functions::euclidean),
+            WorleyDistance::Manhattan => noise.set_distance_function(distance_functions::manhattan),
+            WorleyDistance::Chebyshev => noise.set_distance_function(distance_functions::chebyshev),
+        };
+        Self::with_noise(noise, config.offset, config.bias, &config.midpoint)
+    }
+}
+
+impl NoiseSampler<RidgedMulti<Perlin>> {
+    pub fn new_ridged(config: &RidgedConfig) -> Self {
+        let noise = RidgedMulti::<Perlin>::new(config.seed)
+            .set_octaves(config.detail as usize)
+            .set_frequency(config.frequency as f64)
+            .set_lacunarity(config.neatness as f64)
+            .set_persistence(config.roughness as f64)
+            .set_attenuation(config.attenuation as f64);
+        Self::with_noise(noise, config.offset, config.bias, &config.midpoint)
+    }
+}
+
+impl NoiseSampler<Billow<Perlin>> {
+    pub fn new_billow(config: &BillowConfig) -> Self {
+        let noise = Billow::<Perlin>::new(config.seed)
+            .set_octaves(config.detail as usize)
+            .set_frequency(config.frequency as f64)
+            .set_lacunarity(config.neatness as f64)
+            .set_persistence(config.roughness as f64);
+        Self::with_noise(noise, config.offset, config.bias, &config.midpoint)
+    }
+}
+
+impl<F> Sampler for NoiseSampler<F>
 where
-    N: NoiseFn<f64, 2>,
+    F: NoiseFn<f64, 2> + NoiseFn<f64, 3>,
 {
     fn sample(&self, p: Vec2) -> f32 {
-        let xy = p / self.scale + self.origin;
-        let sample = (self.noise.get([xy.x as f64, xy.y as f64]) + 1.4) / 2.8;
-        // Interpolate value.
-        self.midpoint
-            .lerp(((sample as f32).clamp(0.0, 1.0) + self.bias).clamp(0.0, 1.0))
+        let mut xy = p / self.scale + self.origin;
+        if let Some(warp) = &self.warp {
+            xy = warp.apply(xy);
+        }
+        let sample = NoiseFn::<f64, 2>::get(&self.noise, [xy.x as f64, xy.y as f64]);
+        self.interpolate(sample)
+    }
+
+    fn sample_globe(&self, p: Vec2, sphere: &SphereProjection) -> f32 {
+        // Sample 3D noise on a sphere, keeping the same feature size as on a flat map along the equator.
+        let mut xyz = sphere.to_sphere(p) * sphere.radius() / self.scale + self.origin.extend(0.0);
+        if let Some(warp) = &self.warp {
+            xyz = warp.apply_sphere(xyz);
+        }
+        let sample = NoiseFn::<f64, 3>::get(&self.noise, [xyz.x as f64, xyz.y as f64, xyz.z as f64]);
+        self.interpolate(sample)
     }
 
     fn set_scale(&mut self, scale: f32) {
@@ -192,6 +576,149 @@ fn set_scale(&mut self, scale: f32) {
     }
 }
 
+/// A single tectonic plate.
+struct Plate {
+    position: Vec3,
+    velocity: Vec3,
+    continental: bool,
+}
+
+/// Sample elevation of tectonic plates, raised at convergent boundaries and lowered at divergent boundaries.
+struct TectonicSampler {
+    plates: Vec<Plate>,
+    noise: Fbm<Perlin>,
+    spacing: f32,
+    boundary_width: f32,
+    uplift: f32,
+    rift_depth: f32,
+    warp: f32,
+    roughness: f32,
+    relief_only: bool,
+}
+
+impl TectonicSampler {
+    fn new(config: &TectonicConfig, model: WorldModel, world_size: [u32; 2]) -> Self {
+        let size = Vec2::new(world_size[0] as f32, world_size[1] as f32);
+        let sphere = SphereProjection::new(world_size);
+        let mut rng = WyRand::seed_from_u64(config.seed as u64);
+        let plates = (0..config.plates)
+            .map(|_| {
+                let (u, v) = (rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+                let direction = Vec2::from_angle(rng.gen_range(0.0..TAU)) * rng.gen_range(0.0..1.0);
+                let continental = rng.gen_bool(config.continental_ratio as f64);
+                let (position, velocity) = match model {
+                    WorldModel::Flat => ((Vec2::new(u, v) * size).extend(0.0), direction.extend(0.0)),
+                    WorldModel::Globe => {
+                        // Uniformly distributed point on the sphere, and a tangent velocity.
+                        let (lon, z) = (u * TAU - PI, 1.0 - 2.0 * v);
+                        let r = (1.0 - z * z).sqrt();
+                        let position = Vec3::new(r * lon.cos(), r * lon.sin(), z);
+                        let east = Vec3::new(-lon.sin(), lon.cos(), 0.0);
+                        let north = position.cross(east);
+                        (
+                            position * sphere.radius(),
+                            east * direction.x + north * direction.y,
+                        )
+                    }
+                };
+                Plate {
+                    position,
+                    velocity,
+                    continental,
+                }
+            })
+            .collect();
+        let area = match model {
+            WorldModel::Flat => size.x * size.y,
+            WorldModel::Globe => 4.0 * PI * sphere.radius().powi(2),
+        };
+        Self {
+            plates,
+            noise: Fbm::<Perlin>::new(config.seed).set_octaves(4),
+            spacing: (area / config.plates as f32).sqrt(),
+            boundary_width: config.boundary_width,
+            uplift: config.uplift,
+            rift_depth: config.rift_depth,
+            warp: config.warp,
+            roughness: config.roughness,
+            relief_only: false,
+        }
+    }
+
+    /// Create a sampler that only returns boundary relief, with 0.5 meaning no change.
+    fn new_relief(config: &TectonicConfig, model: WorldModel, world_size: [u32; 2]) -> Self {
+        Self {
+            relief_only: true,
+            ..Self::new(config, model, world_size)
+        }
+    }
+
+    fn noise(&self, p: Vec3, channel: f64) -> f32 {
+        self.noise.get([p.x as f64, p.y as f64, p.z as f64, channel]) as f32
+    }
+
+    /// Sample a point in plate space (tiles for flat maps, sphere scaled to tiles for globes).
+    fn sample_at(&self, p: Vec3, globe: bool) -> f32 {
+        // Warp the point, so that plate boundaries are not straight lines.
+        let n = p / self.spacing;
+        let warp_z = if globe { self.noise(n, 2.0) } else { 0.0 };
+        let p = p + Vec3::new(self.noise(n, 0.0), self.noise(n, 1.0), warp_z) * self.warp * self.spacing;
+        // Find two closest plates.
+        let (mut a, mut b) = (0, 0);
+        let (mut dist_a, mut dist_b) = (f32::MAX, f32::MAX);
+        for (i, plate) in self.plates.iter().enumerate() {
+            let dist = p.distance_squared(plate.position);
+            if dist < dist_a {
+                (b, dist_b) = (a, dist_a);
+                (a, dist_a) = (i, dist);
+            } else if dist < dist_b {
+                (b, dist_b) = (i, dist);
+            }
+        }
+        let plate = &self.plates[a];
+        let base = if plate.continental {
+            CONTINENTAL_ELEVATION
+        } else {
+            OCEANIC_ELEVATION
+        };
+        let mut relief = 0.0;
+        if a != b {
+            let other = &self.plates[b];
+            // Distance to the boundary (bisector of both plate centers).
+            let axis = other.position - plate.position;
+            let distance = (dist_b - dist_a) / (2.0 * axis.length());
+            let falloff = (1.0 - distance / self.boundary_width).max(0.0);
+            let falloff = falloff * falloff * (3.0 - 2.0 * falloff);
+            // Positive when plates move towards each other.
+            let convergence = (plate.velocity - other.velocity).dot(axis.normalize_or_zero());
+            // Convergent boundaries make mountains, divergent make rifts on land and ridges in the ocean.
+            let strength = if convergence > 0.0 {
+                self.uplift * convergence.min(1.0)
+            } else if plate.continental {
+                -self.rift_depth * (-convergence).min(1.0)
+            } else {
+                self.rift_depth * RIDGE_RATIO * (-convergence).min(1.0)
+            };
+            relief = strength * falloff;
+        }
+        if self.relief_only {
+            return (0.5 + relief).clamp(0.0, 1.0);
+        }
+        let detail = self.noise(n * 4.0, 3.0) * self.roughness;
+        (base + relief + detail).clamp(0.0, 1.0)
+    }
+}
+
+impl Sampler for TectonicSampler {
+    fn sample(&self, p: Vec2) -> f32 {
+        self.sample_at(p.extend(0.0), false)
+    }
+
+    fn sample_globe(&self, p: Vec2, sphere: &SphereProjection) -> f32 {
+        self.sample_at(sphere.to_sphere(p) * sphere.radius(), true)
+    }
+}
+
 /// Sample data by transforming point latitude (height) with a 9-point lerp.
 struct LatitudinalSampler {
     pub south_value: f32,
@@ -307,9 +834,45 @@ pub fn fill_latitudinal_precip(
     sample_fill(data, sampler, model, world_size);
 }
 
+/// Layer data value, either a byte or a high precision float (both in 0.0..=255.0 range).
+pub trait LayerValue: Copy {
+    fn from_value(value: f32) -> Self;
+    fn to_value(self) -> f32;
+    /// Add a signed value, saturating at the range bounds.
+    fn add_value(self, value: f32) -> Self;
+}
+
+impl LayerValue for u8 {
+    fn from_value(value: f32) -> Self {
+        value as u8
+    }
+
+    fn to_value(self) -> f32 {
+        self as f32
+    }
+
+    fn add_value(self, value: f32) -> Self {
+        self.saturating_add_signed(value as i8)
+    }
+}
+
+impl LayerValue for f32 {
+    fn from_value(value: f32) -> Self {
+        value.clamp(0.0, 255.0)
+    }
+
+    fn to_value(self) -> f32 {
+        self
+    }
+
+    fn add_value(self, value: f32) -> Self {
+        (self + value).clamp(0.0, 255.0)
+    }
+}
+
 /// Sample the entire world space with a noise sampler and add it to existing data.
 pub fn add_with_algorithm(
-    data: &mut [u8],
+    data: &mut [impl LayerValue],
     model: WorldModel,
     world_size: [u32; 2],
     algorithm: impl AsRef<NoiseAlgorithm>,
@@ -321,28 +884,56 @@ pub fn add_with_algorithm(
     match algorithm.as_ref() {
         NoiseAlgorithm::Perlin(config) => sample_add(
             data,
-            FbmSampler::<Perlin>::new(config),
+            NoiseSampler::<Fbm<Perlin>>::new(config),
             model,
             world_size,
             strength,
         ),
         NoiseAlgorithm::PerlinSurflet(config) => sample_add(
             data,
-            FbmSampler::<PerlinSurflet>::new(config),
+            NoiseSampler::<Fbm<PerlinSurflet>>::new(config),
             model,
             world_size,
             strength,
         ),
         NoiseAlgorithm::OpenSimplex(config) => sample_add(
             data,
-            FbmSampler::<OpenSimplex>::new(config),
+            NoiseSampler::<Fbm<OpenSimplex>>::new(config),
             model,
             world_size,
             strength,
         ),
         NoiseAlgorithm::SuperSimplex(config) => sample_add(
             data,
-            FbmSampler::<SuperSimplex>::new(config),
+            NoiseSampler::<Fbm<SuperSimplex>>::new(config),
+            model,
+            world_size,
+            strength,
+        ),
+        NoiseAlgorithm::Worley(config) => sample_add(
+            data,
+            NoiseSampler::new_worley(config),
+            model,
+            world_size,
+            strength,
+        ),
+        NoiseAlgorithm::RidgedMulti(config) => sample_add(
+            data,
+            NoiseSampler::new_ridged(config),
+            model,
+            world_size,
+            strength,
+        ),
+        NoiseAlgorithm::Billow(config) => sample_add(
+            data,
+            NoiseSampler::new_billow(config),
+            model,
+            world_size,
+            strength,
+        ),
+        NoiseAlgorithm::Tectonic(config) => sample_add(
+            data,
+            TectonicSampler::new(config, model, world_size),
             model,
             world_size,
             strength,
@@ -353,42 +944,148 @@ pub fn add_with_algorithm(
 
 /// Sample the entire world space with a noise sampler and replace existing data.
 pub fn fill_with_algorithm(
-    data: &mut [u8],
+    data: &mut [impl LayerValue],
     model: WorldModel,
     world_size: [u32; 2],
     algorithm: impl AsRef<NoiseAlgorithm>,
 ) {
     match algorithm.as_ref() {
         NoiseAlgorithm::Perlin(config) => {
-            sample_fill(data, FbmSampler::<Perlin>::new(config), model, world_size)
+            sample_fill(data, NoiseSampler::<Fbm<Perlin>>::new(config), model, world_size)
         }
-        NoiseAlgorithm::PerlinSurflet(config) => {
-            sample_fill(data, FbmSampler::<PerlinSurflet>::new(config), model, world_size)
+        NoiseAlgorithm::PerlinSurflet(config) => sample_fill(
+            data,
+            NoiseSampler::<Fbm<PerlinSurflet>>::new(config),
+            model,
+            world_size,
+        ),
+        NoiseAlgorithm::OpenSimplex(config) => sample_fill(
+            data,
+            NoiseSampler::<Fbm<OpenSimplex>>::new(config),
+            model,
+            world_size,
+        ),
+        NoiseAlgorithm::SuperSimplex(config) => sample_fill(
+            data,
+            NoiseSampler::<Fbm<SuperSimplex>>::new(config),
+            model,
+            world_size,
+        ),
+        NoiseAlgorithm::Worley(config) => {
+            sample_fill(data, NoiseSampler::new_worley(config), model, world_size)
         }
-        NoiseAlgorithm::OpenSimplex(config) => {
-            sample_fill(data, FbmSampler::<OpenSimplex>::new(config), model, world_size)
+        NoiseAlgorithm::RidgedMulti(config) => {
+            sample_fill(data, NoiseSampler::new_ridged(config), model, world_size)
         }
-        NoiseAlgorithm::SuperSimplex(config) => {
-            sample_fill(data, FbmSampler::<SuperSimplex>::new(config), model, world_size)
+        NoiseAlgorithm::Billow(config) => {
+            sample_fill(data, NoiseSampler::new_billow(config), model, world_size)
         }
+        NoiseAlgorithm::Tectonic(config) => sample_fill(
+            data,
+            TectonicSampler::new(config, model, world_size),
+            model,
+            world_size,
+        ),
         NoiseAlgorithm::FromImage => { /* Do nothing. */ }
     }
 }
 
-/// Fill an influence layer with specified shape (or noise algorithm).
-pub fn fill_influence(data: &mut [u8], shape: &InfluenceShape, model: WorldModel, world_size: [u32; 2]) {
-    match shape {
-        InfluenceShape::None => data.fill(0),
-        InfluenceShape::FromImage(_) => { /*Do nothing. */ }
-        InfluenceShape::Circle(x) => sample_fill(data, CircleSampler::new(x), model, world_size),
-        InfluenceShape::Strip(x) => sample_fill(data, StripSampler::new(x), model, world_size),
-        InfluenceShape::Fbm(x) => fill_with_algorithm(data, model, world_size, x),
+/// Raise mountains and lower rifts along tectonic plate boundaries in existing data.
+pub fn add_tectonic_relief(
+    data: &mut [impl LayerValue],
+    model: WorldModel,
+    world_size: [u32; 2],
+    config: &TectonicConfig,
+) {
+    let sampler = TectonicSampler::new_relief(config, model, world_size);
+    sample_add(data, sampler, model, world_size, 1.0);
+}
+
+/// Fill an influence layer with specified shapes (or noise algorithms), combined into one map.
+/// Shapes from image use the raw image loaded for the layer, or an empty map if there is none.
+pub fn fill_influence(
+    data: &mut [u8],
+    image: Option<&[u8]>,
+    config: &InfluenceConfig,
+    model: WorldModel,
+    world_size: [u32; 2],
+) {
+    let mut shape_data = vec![0u8; data.len()];
+    let mut result: Option<Vec<f32>> = None;
+    for shape in config.shapes.iter().map(|x| &x.shape) {
+        let Some((mode, strength)) = shape.get_mode_strength() else {
+            continue;
+        };
+        match shape {
+            InfluenceShape::None => unreachable!(),
+            InfluenceShape::FromImage(_) => match image {
+                Some(image) => shape_data.copy_from_slice(image),
+                None => shape_data.fill(0),
+            },
+            InfluenceShape::Circle(x) => {
+                sample_fill(&mut shape_data, CircleSampler::new(x), model, world_size)
+            }
+            InfluenceShape::Strip(x) => sample_fill(&mut shape_data, StripSampler::new(x), model, world_size),
+            InfluenceShape::Polygon(x) => {
+                sample_fill(&mut shape_data, PolygonSampler::new(x), model, world_size)
+            }
+            InfluenceShape::Spline(x) => {
+                sample_fill(&mut shape_data, SplineSampler::new(x), model, world_size)
+            }
+            InfluenceShape::Fbm(x) => fill_with_algorithm(&mut shape_data, model, world_size, x),
+        }
+        let values = shape_data
+            .iter()
+            .map(|x| to_neutral_influence(*x, mode, strength));
+        result = Some(match result {
+            None => values.collect(),
+            Some(mut result) => {
+                for (value, other) in result.iter_mut().zip(values) {
+                    *value = composite_influence(*value, other, config.compositing);
+                }
+                result
+            }
+        });
+    }
+    match result {
+        Some(result) => {
+            for (value, x) in data.iter_mut().zip(result) {
+                *value = (x * 255.0) as u8;
+            }
+        }
+        None => data.fill(0),
+    }
+}
+
+/// Helper function
+///
+/// Convert an influence value into a value for [`InfluenceMode::ScaleUpDown`] with full strength,
+/// so that shapes with different modes can be combined. 0.5 means no change.
+fn to_neutral_influence(value: u8, mode: InfluenceMode, strength: f32) -> f32 {
+    let value = value as f32 / 255.0;
+    let strength = strength.clamp(0.0, 1.0);
+    match mode {
+        InfluenceMode::ScaleDown => (1.0 - (1.0 - value) * strength) / 2.0,
+        InfluenceMode::ScaleUp => 0.5 + value * strength / 2.0,
+        InfluenceMode::ScaleUpDown => 0.5 + (value - 0.5) * strength,
+    }
+}
+
+/// Helper function
+///
+/// Combine two neutral influence values (see [`to_neutral_influence`]).
+fn composite_influence(a: f32, b: f32, compositing: InfluenceCompositing) -> f32 {
+    match compositing {
+        InfluenceCompositing::Max => a.max(b),
+        InfluenceCompositing::Add => (a + b - 0.5).clamp(0.0, 1.0),
+        // Values represent scale factors in [0.0, 2.0] range.
+        InfluenceCompositing::Multiply => (2.0 * a * b).clamp(0.0, 1.0),
     }
 }
 
 /// Apply influence data to real data with given influence strength.
 /// Strength == 0.0 means no effect, strength == 1.0 means max effect.
-pub fn apply_influence(data: &mut [u8], influence: &[u8], mode: InfluenceMode, strength: f32) {
+pub fn apply_influence(data: &mut [impl LayerValue], influence: &[u8], mode: InfluenceMode, strength: f32) {
     let strength = strength.clamp(0.0, 1.0);
     if strength.is_zero() {
         return;
@@ -398,25 +1095,25 @@ pub fn apply_influence(data: &mut [u8], influence: &[u8], mode: InfluenceMode, s
         InfluenceMode::ScaleDown => {
             for i in 0..data.len() {
                 let inf = 1.0 - (1.0 - influence[i] as f32 / 255.0) * strength;
-                data[i] = lerp(0.0..=(data[i] as f32), inf) as u8;
+                data[i] = LayerValue::from_value(lerp(0.0..=data[i].to_value(), inf));
             }
         }
         // Scale "good" influence to 1
         InfluenceMode::ScaleUp => {
             for i in 0..data.len() {
                 let inf = (influence[i] as f32 / 255.0) * strength;
-                data[i] = lerp((data[i] as f32)..=255.0, inf) as u8;
+                data[i] = LayerValue::from_value(lerp(data[i].to_value()..=255.0, inf));
             }
         }
         // Scale both "bad" and "good" influence with baseline being 0.5
         InfluenceMode::ScaleUpDown => {
             for i in 0..data.len() {
                 let inf = (influence[i] as f32 / 255.0 - 0.5) * strength;
-                data[i] = if inf <= 0.0 {
-                    lerp(0.0..=(data[i] as f32), 1.0 + inf * 2.0)
+                data[i] = LayerValue::from_value(if inf <= 0.0 {
+                    lerp(0.0..=data[i].to_value(), 1.0 + inf * 2.0)
                 } else {
-                    lerp((data[i] as f32)..=255.0, inf * 2.0)
-                } as u8;
+                    lerp(data[i].to_value()..=255.0, inf * 2.0)
+                });
             }
         }
     }
@@ -424,9 +1121,9 @@ pub fn apply_influence(data: &mut [u8], influence: &[u8], mode: InfluenceMode, s
 
 /// Apply influence data to real data with given influence strength.
 /// Strength == 0.0 means no effect, strength == 1.0 means max effect.
-pub fn apply_influence_from_src(
-    dest: &mut [u8],
-    src: &[u8],
+pub fn apply_influence_from_src<T: LayerValue>(
+    dest: &mut [T],
+    src: &[T],
     influence: &[u8],
     mode: InfluenceMode,
     strength: f32,
@@ -440,79 +1137,84 @@ pub fn apply_influence_from_src(
         InfluenceMode::ScaleDown => {
             for i in 0..dest.len() {
                 let inf = 1.0 - (1.0 - influence[i] as f32 / 255.0) * strength;
-                dest[i] = lerp(0.0..=(src[i] as f32), inf) as u8;
+                dest[i] = T::from_value(lerp(0.0..=src[i].to_value(), inf));
             }
         }
         // Scale "good" influence to 1
         InfluenceMode::ScaleUp => {
             for i in 0..dest.len() {
                 let inf = (influence[i] as f32 / 255.0) * strength;
-                dest[i] = lerp((src[i] as f32)..=255.0, inf) as u8;
+                dest[i] = T::from_value(lerp(src[i].to_value()..=255.0, inf));
             }
         }
         // Scale both "bad" and "good" influence with baseline being 0.5
         InfluenceMode::ScaleUpDown => {
             for i in 0..dest.len() {
                 let inf = (influence[i] as f32 / 255.0 - 0.5) * strength;
-                dest[i] = if inf <= 0.0 {
-                    lerp(0.0..=(src[i] as f32), 1.0 + inf * 2.0)
+                dest[i] = T::from_value(if inf <= 0.0 {
+                    lerp(0.0..=src[i].to_value(), 1.0 + inf * 2.0)
                 } else {
-                    lerp((src[i] as f32)..=255.0, inf * 2.0)
-                } as u8;
+                    lerp(src[i].to_value()..=255.0, inf * 2.0)
+                });
             }
         }
     }
 }
 
 fn sample_add(
-    data: &mut [u8],
+    data: &mut [impl LayerValue],
     mut sampler: impl Sampler,
     model: WorldModel,
     world_size: [u32; 2],
     strength: f32,
 ) {
-    match model {
-        WorldModel::Flat => {
-            let width = world_size[0] as i32;
-            let height = world_size[1] as i32;
-            // NOTE: Not respected by FbmSampler.
-            let origin = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
-            // NOTE: Only respected by FbmSampler.
-            let scale = f32::sqrt((width * height) as f32);
-            sampler.offset_origin(origin);
-            sampler.set_scale(scale);
-            for y in 0..height {
-                for x in 0..width {
-                    let i = (y * width + x) as usize;
-                    let p = Vec2::new(x as f32, y as f32);
-                    let value = ((sampler.sample(p) - 0.5) * 127f32 * strength) as i8;
-                    data[i] = data[i].saturating_add_signed(value);
-                }
-            }
+    let width = world_size[0] as i32;
+    let height = world_size[1] as i32;
+    // NOTE: Not respected by NoiseSampler.
+    let origin = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
+    // NOTE: Only respected by NoiseSampler.
+    let scale = f32::sqrt((width * height) as f32);
+    sampler.offset_origin(origin);
+    sampler.set_scale(scale);
+    let sphere = SphereProjection::new(world_size);
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) as usize;
+            let p = Vec2::new(x as f32, y as f32);
+            let sample = sample_tile(&sampler, p, model, &sphere);
+            data[i] = data[i].add_value((sample - 0.5) * 127f32 * strength);
         }
-        WorldModel::Globe => todo!(), // TODO
     }
 }
 
-fn sample_fill(data: &mut [u8], mut sampler: impl Sampler, model: WorldModel, world_size: [u32; 2]) {
-    match model {
-        WorldModel::Flat => {
-            let width = world_size[0] as i32;
-            let height = world_size[1] as i32;
-            // NOTE: Not respected by FbmSampler.
-            let origin = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
-            // NOTE: Only respected by FbmSampler.
-            let scale = f32::sqrt((width * height) as f32);
-            sampler.offset_origin(origin);
-            sampler.set_scale(scale);
-            for y in 0..height {
-                for x in 0..width {
-                    let i = (y * width + x) as usize;
-                    let p = Vec2::new(x as f32, y as f32);
-                    data[i] = (sampler.sample(p) * 255f32) as u8;
-                }
-            }
+fn sample_fill(
+    data: &mut [impl LayerValue],
+    mut sampler: impl Sampler,
+    model: WorldModel,
+    world_size: [u32; 2],
+) {
+    let width = world_size[0] as i32;
+    let height = world_size[1] as i32;
+    // NOTE: Not respected by NoiseSampler.
+    let origin = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
+    // NOTE: Only respected by NoiseSampler.
+    let scale = f32::sqrt((width * height) as f32);
+    sampler.offset_origin(origin);
+    sampler.set_scale(scale);
+    let sphere = SphereProjection::new(world_size);
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) as usize;
+            let p = Vec2::new(x as f32, y as f32);
+            data[i] = LayerValue::from_value(sample_tile(&sampler, p, model, &sphere) * 255f32);
         }
-        WorldModel::Globe => todo!(), // TODO
+    }
+}
+
+/// Sample a single tile, either on a flat map or projected onto a sphere.
+fn sample_tile(sampler: &impl Sampler, p: Vec2, model: WorldModel, sphere: &SphereProjection) -> f32 {
+    match model {
+        WorldModel::Flat => sampler.sample(p),
+        WorldModel::Globe => sampler.sample_globe(p, sphere),
     }
 }
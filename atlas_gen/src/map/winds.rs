@@ -0,0 +1,76 @@
+use atlas_lib::{
+    bevy::utils::petgraph::matrix_graph::Zero,
+    config::{gen::RainShadowConfig, WorldModel},
+    domain::map::is_sea,
+};
+
+/// Upper limit of the precipitation multiplier on windward slopes.
+const MAX_WINDWARD_FACTOR: f32 = 2.0;
+
+/// Carry moisture along prevailing winds and scale precipitation accordingly.
+/// Air loses moisture when rising over terrain (and slowly over any land) and regains it over sea,
+/// which makes windward slopes wetter and lee sides drier.
+pub fn apply_rain_shadow(
+    data: &mut [u8],
+    topo_data: &[u8],
+    cont_data: &[u8],
+    config: &RainShadowConfig,
+    model: WorldModel,
+    world_size: [u32; 2],
+) {
+    let (width, height) = (world_size[0] as usize, world_size[1] as usize);
+    if config.strength.is_zero() {
+        return;
+    }
+    // Globes wrap around east-west, so air goes around once before it's applied, to settle its moisture.
+    let laps = match model {
+        WorldModel::Flat => 1,
+        WorldModel::Globe => 2,
+    };
+    for y in 0..height {
+        let wind = config.winds.wind_at(row_latitude(y, height));
+        let strength = config.strength * wind.abs();
+        if strength.is_zero() {
+            continue;
+        }
+        // Air enters the map from the upwind edge fully saturated, as if it came from the sea.
+        let mut moisture = 1.0f32;
+        let mut previous = 0u8;
+        let (start, direction) = if wind > 0.0 {
+            (y * width, 1)
+        } else {
+            (y * width + width - 1, -1)
+        };
+        let mut next = Some(start as u32);
+        for step in 0..width * laps {
+            let Some(i) = next else {
+                break;
+            };
+            next = model.offset_tile((world_size[0], world_size[1]), i, (direction, 0));
+            let i = i as usize;
+            let factor = if is_sea(cont_data[i]) {
+                moisture += (1.0 - moisture) * config.sea_gain;
+                1.0
+            } else {
+                let rise = topo_data[i].saturating_sub(previous) as f32;
+                let loss = moisture * (rise * config.uplift_loss + config.land_loss).min(1.0);
+                moisture -= loss;
+                // Moisture lost here falls as extra rain on this tile.
+                (moisture + loss * config.windward_boost).min(MAX_WINDWARD_FACTOR)
+            };
+            previous = topo_data[i];
+            if step < width * (laps - 1) {
+                continue;
+            }
+            let value = data[i] as f32 * (1.0 - strength + strength * factor);
+            data[i] = value.clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Helper function
+///
+/// Get latitude (in degrees) of the middle of a map row.
+pub fn row_latitude(y: usize, height: usize) -> f32 {
+    90.0 - 180.0 * (y as f32 + 0.5) / height as f32
+}
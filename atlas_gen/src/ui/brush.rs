@@ -0,0 +1,238 @@
+use atlas_lib::{
+    base::{
+        events::EventStruct,
+        ui::{MainCamera, UiStateBase},
+    },
+    bevy::{prelude::*, window::PrimaryWindow},
+    bevy_egui::{self, egui::lerp, EguiContexts},
+    config::{gen::AtlasGenConfig, AtlasConfig, WorldModel, NEIGHBOURS_8},
+    domain::{
+        graphics::{CurrentWorldModel, MapLogicData},
+        map::MapDataLayer,
+    },
+    ui::{sidebar::*, UiEditableEnum},
+    MakeUi, UiEditableEnum,
+};
+
+use crate::ui::AtlasGenUi;
+
+/// Max brush radius, in tiles.
+const MAX_BRUSH_RADIUS: u32 = 500;
+/// How much raise/lower brush changes values per second at full strength.
+const BRUSH_SPEED: f32 = 100.0;
+/// How fast smooth/flatten/set brush approaches the target value at full strength.
+const BRUSH_RATE: f32 = 10.0;
+
+/// Brush tool settings.
+#[derive(Debug, MakeUi)]
+pub struct BrushSettings {
+    #[name("Enable Brush (LMB)")]
+    #[control(SidebarCheckbox)]
+    pub enabled: bool,
+    #[name("Brush Mode")]
+    #[control(SidebarEnumDropdown)]
+    pub mode: BrushMode,
+    #[name("Radius")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=MAX_BRUSH_RADIUS))]
+    pub radius: u32,
+    #[name("Strength")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub strength: f32,
+    #[name("Set Value")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=255))]
+    pub value: u8,
+}
+
+impl Default for BrushSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: Default::default(),
+            radius: 10,
+            strength: 0.5,
+            value: 128,
+        }
+    }
+}
+
+/// How the brush affects layer values.
+#[derive(Clone, Copy, Debug, Default, UiEditableEnum)]
+pub enum BrushMode {
+    /// Increase values.
+    #[default]
+    Raise,
+    /// Decrease values.
+    Lower,
+    /// Move values towards the average of their neighbours.
+    Smooth,
+    /// Move values towards the value at the stroke start.
+    Flatten,
+    /// Move values towards the "Set Value" setting.
+    Set,
+}
+
+/// A brush stroke in progress.
+pub struct BrushStroke {
+    /// Painted layer.
+    layer: MapDataLayer,
+    /// Layer data with full precision, so that small changes accumulate.
+    data: Vec<f32>,
+    /// Target value for the flatten mode.
+    flatten_value: f32,
+}
+
+/// Update system
+///
+/// Paint on the currently viewed layer with the brush tool.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn update_brush(
+    mut ui_state: ResMut<AtlasGenUi>,
+    ui_base: Res<UiStateBase>,
+    mut logics: ResMut<MapLogicData>,
+    mut events: ResMut<EventStruct>,
+    mut contexts: EguiContexts,
+    config: Res<AtlasGenConfig>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    map: Query<&Transform, With<CurrentWorldModel>>,
+) {
+    let ui_state = ui_state.as_mut();
+    // Stroke has ended, keep painted data with full precision and refresh dependant layers.
+    if !mouse_button.pressed(MouseButton::Left) {
+        if let Some(stroke) = ui_state.stroke.take() {
+            logics.put_layer_precise(stroke.layer, stroke.data);
+            events.brush_stroke_request = Some(stroke.layer);
+        }
+        return;
+    }
+    let layer = ui_base.current_layer;
+    let flat = matches!(config.general.preview_model, WorldModel::Flat);
+    if !ui_state.brush.enabled || !flat || !is_paintable(layer) {
+        return;
+    }
+    let Some(tile) = get_cursor_tile(&window, &camera, &map, &ui_base, config.as_ref()) else {
+        return;
+    };
+    // Start a new stroke, unless the click was meant for the UI.
+    if mouse_button.just_pressed(MouseButton::Left) && !contexts.ctx_mut().is_pointer_over_area() {
+        let data = logics.get_layer_precise(layer);
+        ui_state.stroke = Some(BrushStroke {
+            layer,
+            flatten_value: data[tile as usize],
+            data,
+        });
+    }
+    let Some(stroke) = &mut ui_state.stroke else {
+        return;
+    };
+    let data = logics.get_layer_mut(stroke.layer);
+    apply_brush(
+        data,
+        stroke,
+        &ui_state.brush,
+        config.as_ref(),
+        tile,
+        time.delta_seconds(),
+    );
+    // Refresh the layer texture.
+    events.regen_layer_request = Some(vec![stroke.layer]);
+}
+
+/// Helper function
+///
+/// Check if a layer can be painted on. Derived and color coded layers cannot.
+fn is_paintable(layer: MapDataLayer) -> bool {
+    !matches!(
+        layer,
+        MapDataLayer::Preview
+            | MapDataLayer::RealTopography
+            | MapDataLayer::TopographyFilter
+            | MapDataLayer::Deposits
+            | MapDataLayer::Climate
+            | MapDataLayer::Rivers
+    )
+}
+
+/// Helper function
+///
+/// Get index of the map tile under the mouse cursor, if there is one.
+fn get_cursor_tile(
+    window: &Query<&Window, With<PrimaryWindow>>,
+    camera: &Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    map: &Query<&Transform, With<CurrentWorldModel>>,
+    ui_base: &UiStateBase,
+    config: &AtlasGenConfig,
+) -> Option<u32> {
+    let (camera, camera_transform) = camera.single();
+    let map = map.single();
+    // Check if the mouse cursor is inside the viewport.
+    let cursor_position = window.single().cursor_position()?;
+    if (cursor_position[0] > ui_base.viewport_size[0])
+        || (cursor_position[1] > ui_base.viewport_size[1])
+        || ui_base.file_dialog.is_some()
+    {
+        return None;
+    }
+    // Raycast from the camera.
+    let ray = camera.viewport_to_world(camera_transform, cursor_position)?;
+    let distance = ray.intersect_plane(map.translation, Plane3d::new(*map.up()))?;
+    let coords = ray.get_point(distance);
+    config
+        .world_to_map((coords.x, coords.y))
+        .map(|x| config.map_to_index(x))
+}
+
+/// Helper function
+///
+/// Apply the brush around a tile, for a given time step.
+fn apply_brush(
+    data: &mut [u8],
+    stroke: &mut BrushStroke,
+    brush: &BrushSettings,
+    config: &AtlasGenConfig,
+    center: u32,
+    delta: f32,
+) {
+    let radius = brush.radius as i32;
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            if distance > brush.radius as f32 {
+                continue;
+            }
+            let Some(i) = config.get_offset_tile(center, (dx, dy)) else {
+                continue;
+            };
+            // Smooth falloff from brush center to its edge.
+            let falloff = 1.0 - distance / brush.radius as f32;
+            let falloff = falloff * falloff * (3.0 - 2.0 * falloff);
+            let amount = brush.strength * falloff * delta;
+            let rate = (amount * BRUSH_RATE).min(1.0);
+            let value = stroke.data[i as usize];
+            let value = match brush.mode {
+                BrushMode::Raise => value + amount * BRUSH_SPEED,
+                BrushMode::Lower => value - amount * BRUSH_SPEED,
+                BrushMode::Smooth => {
+                    let (sum, count) = NEIGHBOURS_8
+                        .iter()
+                        .filter_map(|x| config.get_offset_tile(i, *x))
+                        .fold((value, 1.0), |(sum, count), j| {
+                            (sum + stroke.data[j as usize], count + 1.0)
+                        });
+                    lerp(value..=(sum / count), rate)
+                }
+                BrushMode::Flatten => lerp(value..=stroke.flatten_value, rate),
+                BrushMode::Set => lerp(value..=(brush.value as f32), rate),
+            }
+            .clamp(0.0, 255.0);
+            stroke.data[i as usize] = value;
+            data[i as usize] = value.round() as u8;
+        }
+    }
+}
@@ -8,10 +8,10 @@
     bevy::prelude::*,
     bevy_egui::egui::Ui,
     config::{
-        gen::{AtlasGenConfig, InfluenceShape},
-        load_config, load_image, load_image_grey, save_config,
+        gen::{AtlasGenConfig, InfluenceConfig},
+        load_config, load_image, load_image_grey, load_image_grey_precise, save_config,
     },
-    domain::map::MapDataLayer,
+    domain::map::{MapDataLayer, EXPORT_PRECISE_LAYERS},
     ui::{button, sidebar::SidebarPanel},
 };
 
@@ -19,9 +19,9 @@
 
 /// A sidebar page/panel.
 pub trait SidebarPanelGen: SidebarPanel<AtlasGenConfig, AtlasGenUi> {
-    /// Get influence shape from this panel's config. [`InfluenceShape::None`] by default.
-    fn get_influence_shape<'b>(&self, _config: &'b AtlasGenConfig) -> &'b InfluenceShape {
-        &InfluenceShape::None
+    /// Get influence config from this panel's config. [`None`] by default.
+    fn get_influence<'b>(&self, _config: &'b AtlasGenConfig) -> Option<&'b InfluenceConfig> {
+        None
     }
 
     /// Create a "Generate Layer" button.
@@ -32,8 +32,8 @@ fn button_layer(&self, ui: &mut Ui, events: &mut EventStruct) {
     }
 
     /// Create a "Generate Influence Map" button.
-    fn button_influence(&self, ui: &mut Ui, events: &mut EventStruct, influence: &InfluenceShape) {
-        if !matches!(influence, InfluenceShape::None) && button(ui, "Generate Influence Map") {
+    fn button_influence(&self, ui: &mut Ui, events: &mut EventStruct, influence: Option<&InfluenceConfig>) {
+        if influence.is_some_and(|x| x.is_used()) && button(ui, "Generate Influence Map") {
             if let Some(layer) = self.get_layer().get_influence_layer() {
                 events.generate_request = Some((layer, false));
             }
@@ -74,8 +74,10 @@ macro_rules! reset_panel {
         MapDataLayer::Topography => reset_panel!(topography, Topography),
         MapDataLayer::Temperature => reset_panel!(temperature, Temperature),
         MapDataLayer::Precipitation => reset_panel!(precipitation, Precipitation),
+        MapDataLayer::Rivers => reset_panel!(rivers, Rivers),
         MapDataLayer::Climate => reset_panel!(climate, Climate),
         MapDataLayer::Deposits => reset_panel!(deposits, Deposits),
+        MapDataLayer::Custom(_) => config.custom = default(),
         _ => unreachable!(),
     }
 }
@@ -114,6 +116,14 @@ fn load_layer_data(&mut self, path: &Path, layer: MapDataLayer) {
             self.config.general.world_size[0],
             self.config.general.world_size[1],
         );
+        // Keep full precision of 16-bit images for topography.
+        if EXPORT_PRECISE_LAYERS.iter().any(|(x, _)| *x == layer) {
+            match load_image_grey_precise(path, width, height) {
+                Ok(data) => self.events.load_layer_precise_request = Some((layer, data)),
+                Err(err) => self.events.error_window = Some(err.to_string()),
+            };
+            return;
+        }
         let result = match layer {
             MapDataLayer::Preview => load_image(path, width, height),
             _ => load_image_grey(path, width, height),
@@ -136,6 +146,14 @@ fn export(&mut self, path: &Path) {
         self.events.export_world_request = Some(path.into());
     }
 
+    fn export_gis(&mut self, path: &Path) {
+        self.events.export_gis_request = Some(path.into());
+    }
+
+    fn export_mesh(&mut self, path: &Path) {
+        self.events.export_mesh_request = Some(path.into());
+    }
+
     fn import(&mut self, path: &Path) {
         self.events.import_world_request = Some(path.into());
     }
@@ -143,4 +161,16 @@ fn import(&mut self, path: &Path) {
     fn import_special(&mut self, _path: &Path) {
         unreachable!()
     }
+
+    fn save_stats(&mut self, _path: &Path) {
+        unreachable!()
+    }
+
+    fn save_chronicle(&mut self, _path: &Path) {
+        unreachable!()
+    }
+
+    fn save_timelapse(&mut self, _path: &Path) {
+        unreachable!()
+    }
 }
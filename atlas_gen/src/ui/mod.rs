@@ -1,3 +1,4 @@
+mod brush;
 mod internal;
 mod panels;
 
@@ -11,12 +12,17 @@
     },
     bevy::{app::AppExit, ecs as bevy_ecs, prelude::*},
     bevy_egui::{
-        egui::{self, Context, RichText, Ui},
+        egui::{self, Context, Grid, RichText, Ui},
         EguiContexts,
     },
-    config::gen::AtlasGenConfig,
-    ui::{button_action, sidebar::SidebarPanel, window},
+    config::{gen::AtlasGenConfig, AtlasConfig},
+    ui::{
+        button_action,
+        sidebar::{MakeUi, SidebarPanel},
+        window,
+    },
 };
+use brush::{update_brush, BrushSettings, BrushStroke};
 use internal::{clear_layer_clicked, reset_config_clicked, reset_panel_clicked, FileDialogHandler};
 use panels::*;
 
@@ -27,7 +33,7 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(UiPluginBase)
             .init_resource::<AtlasGenUi>()
-            .add_systems(UiUpdate, (update_ui, update_viewport).chain());
+            .add_systems(UiUpdate, (update_ui, update_viewport, update_brush).chain());
     }
 }
 
@@ -46,6 +52,7 @@ fn update_ui(
     if !window.single().focused {
         return;
     }
+    ui_base.custom_layers = config.get_custom_layers().into_iter().map(|x| x.name).collect();
     ui_state.create_ui(
         contexts.ctx_mut(),
         &mut config,
@@ -59,12 +66,18 @@ fn update_ui(
 struct AtlasGenUi {
     /// Currently viewed sidebar panel.
     pub current_panel: Box<dyn SidebarPanel<AtlasGenConfig, Self> + Sync + Send>,
+    /// Brush tool settings.
+    pub brush: BrushSettings,
+    /// Brush stroke in progress.
+    pub stroke: Option<BrushStroke>,
 }
 
 impl Default for AtlasGenUi {
     fn default() -> Self {
         Self {
             current_panel: Box::<MainPanelGeneral>::default(),
+            brush: Default::default(),
+            stroke: None,
         }
     }
 }
@@ -88,6 +101,12 @@ fn create_sidebar_head(
                     button_action(ui, "Export World", || {
                         open_file_dialog(ui_base, FileDialogMode::Export)
                     });
+                    button_action(ui, "Export World (GIS)", || {
+                        open_file_dialog(ui_base, FileDialogMode::ExportGis)
+                    });
+                    button_action(ui, "Export Terrain Mesh", || {
+                        open_file_dialog(ui_base, FileDialogMode::ExportMesh)
+                    });
                     button_action(ui, "Exit", || {
                         exit.send(AppExit);
                     });
@@ -147,9 +166,13 @@ macro_rules! tab {
             });
             egui::menu::bar(ui, |ui| {
                 tab!("Precipitation", MainPanelPrecipitation, ui);
+                tab!("Rivers", MainPanelRivers, ui);
                 tab!("Climate", MainPanelClimate, ui);
                 tab!("Deposits", MainPanelDeposits, ui);
             });
+            egui::menu::bar(ui, |ui| {
+                tab!("Custom", MainPanelCustom, ui);
+            });
             if changed {
                 let layer = self.current_panel.get_layer();
                 events.viewed_layer_changed = Some(layer);
@@ -159,6 +182,10 @@ macro_rules! tab {
     }
 
     fn create_current_panel(&mut self, ui: &mut Ui, config: &mut AtlasGenConfig, events: &mut EventStruct) {
+        // Brush tool settings.
+        ui.collapsing("Brush Tool", |ui| {
+            Grid::new("brush_panel").show(ui, |ui| self.brush.make_ui(ui));
+        });
         // Panel heading.
         ui.heading(self.current_panel.get_heading());
         // Panel inner.
@@ -1,7 +1,7 @@
 use atlas_lib::{
     base::events::EventStruct,
     bevy_egui::egui::{Grid, Ui},
-    config::r#gen::{AtlasGenConfig, InfluenceShape},
+    config::r#gen::{AtlasGenConfig, InfluenceConfig},
     domain::map::MapDataLayer,
     ui::{
         button,
@@ -20,6 +20,7 @@ macro_rules! make_panel {
         impl SidebarPanel<AtlasGenConfig, AtlasGenUi> for $cls {
             fn make_ui(&mut self, ui: &mut Ui, config: &mut AtlasGenConfig) {
                 config.$field.make_ui(ui);
+                config.$field.influence.ensure_shapes();
             }
 
             fn extra_ui_pre(
@@ -29,7 +30,7 @@ fn extra_ui_pre(
                 _ui_state: &mut AtlasGenUi,
                 events: &mut EventStruct,
             ) {
-                self.button_influence(ui, events, self.get_influence_shape(config));
+                self.button_influence(ui, events, self.get_influence(config));
                 self.button_layer(ui, events);
             }
 
@@ -43,8 +44,8 @@ fn get_layer(&self) -> MapDataLayer {
         }
 
         impl SidebarPanelGen for $cls {
-            fn get_influence_shape<'b>(&self, config: &'b AtlasGenConfig) -> &'b InfluenceShape {
-                &config.$field.influence_shape
+            fn get_influence<'b>(&self, config: &'b AtlasGenConfig) -> Option<&'b InfluenceConfig> {
+                Some(&config.$field.influence)
             }
         }
     };
@@ -105,6 +106,36 @@ fn get_layer(&self) -> MapDataLayer {
 
 impl SidebarPanelGen for MainPanelGeneral {}
 
+/// Panel with river and lake generation settings.
+#[derive(Default, Clone, Copy)]
+pub struct MainPanelRivers;
+
+impl SidebarPanel<AtlasGenConfig, AtlasGenUi> for MainPanelRivers {
+    fn make_ui(&mut self, ui: &mut Ui, config: &mut AtlasGenConfig) {
+        config.rivers.make_ui(ui);
+    }
+
+    fn extra_ui_pre(
+        &mut self,
+        ui: &mut Ui,
+        _config: &mut AtlasGenConfig,
+        _ui_state: &mut AtlasGenUi,
+        events: &mut EventStruct,
+    ) {
+        self.button_layer(ui, events);
+    }
+
+    fn get_heading(&self) -> &'static str {
+        "Rivers"
+    }
+
+    fn get_layer(&self) -> MapDataLayer {
+        MapDataLayer::Rivers
+    }
+}
+
+impl SidebarPanelGen for MainPanelRivers {}
+
 /// Panel with climate generation settings.
 #[derive(Default, Clone, Copy)]
 pub struct MainPanelClimate;
@@ -182,3 +213,39 @@ fn button_layer(&self, ui: &mut Ui, events: &mut EventStruct) {
         }
     }
 }
+
+/// Panel with user-defined custom layer settings.
+#[derive(Default, Clone, Copy)]
+pub struct MainPanelCustom;
+
+impl SidebarPanel<AtlasGenConfig, AtlasGenUi> for MainPanelCustom {
+    fn make_ui(&mut self, ui: &mut Ui, config: &mut AtlasGenConfig) {
+        config.custom.make_ui(ui);
+        config.custom.ensure_layers();
+    }
+
+    fn extra_ui_pre(
+        &mut self,
+        ui: &mut Ui,
+        config: &mut AtlasGenConfig,
+        _ui_state: &mut AtlasGenUi,
+        events: &mut EventStruct,
+    ) {
+        // One button per declared layer.
+        for (i, layer) in config.custom.get_layers().iter().enumerate() {
+            if button(ui, format!("Generate \"{}\"", layer.name)) {
+                events.generate_request = Some((MapDataLayer::Custom(i as u8), false));
+            }
+        }
+    }
+
+    fn get_heading(&self) -> &'static str {
+        "Custom Layers"
+    }
+
+    fn get_layer(&self) -> MapDataLayer {
+        MapDataLayer::Custom(0)
+    }
+}
+
+impl SidebarPanelGen for MainPanelCustom {}
@@ -24,6 +24,8 @@ pub struct EventStruct {
     pub viewed_layer_changed: Option<MapDataLayer>,
     /// A map layer should be loaded from data.
     pub load_layer_request: Option<(MapDataLayer, Vec<u8>)>,
+    /// A map layer should be loaded from high precision data.
+    pub load_layer_precise_request: Option<(MapDataLayer, Vec<f32>)>,
     /// A map layer should be saved to file.
     pub save_layer_request: Option<(MapDataLayer, Box<Path>)>,
     /// A map layer should be rendered to file.
@@ -34,6 +36,8 @@ pub struct EventStruct {
     pub regen_layer_request: Option<Vec<MapDataLayer>>,
     /// This map layer requests data generation (should the influence layer be regenerated too?).
     pub generate_request: Option<(MapDataLayer, bool)>,
+    /// A brush stroke on this map layer has ended, and dependant layers should be refreshed.
+    pub brush_stroke_request: Option<MapDataLayer>,
     /// "climatemap.png" should be reloaded.
     pub load_climatemap_request: Option<()>,
     /// The initial world map should be imported from files.
@@ -42,10 +46,20 @@ pub struct EventStruct {
     pub import_world_request: Option<Box<Path>>,
     /// The whole world should be exported to files.
     pub export_world_request: Option<Box<Path>>,
+    /// The world should be exported to GIS raster files.
+    pub export_gis_request: Option<Box<Path>>,
+    /// The terrain should be exported to 3D mesh files.
+    pub export_mesh_request: Option<Box<Path>>,
     /// The scenario starting points should be randomized.
     pub randomize_starts_request: Option<()>,
     /// The simulation should begin.
     pub simulation_start_request: Option<()>,
+    /// Recorded simulation statistics should be exported to a file.
+    pub export_stats_request: Option<Box<Path>>,
+    /// The chronicle should be exported to a file.
+    pub export_chronicle_request: Option<Box<Path>>,
+    /// Captured timelapse frames should be exported to a directory.
+    pub export_timelapse_request: Option<Box<Path>>,
     /// An error has occured, and a popup window should display it.
     pub error_window: Option<String>,
 }
@@ -56,17 +70,24 @@ fn default() -> Self {
             world_model_changed: None,
             viewed_layer_changed: None,
             load_layer_request: None,
+            load_layer_precise_request: None,
             save_layer_request: None,
             render_layer_request: None,
             clear_layer_request: None,
             regen_layer_request: None,
             generate_request: None,
+            brush_stroke_request: None,
             load_climatemap_request: Some(()), // Load climate map on app start.
             import_start_request: None,
             import_world_request: None,
             export_world_request: None,
+            export_gis_request: None,
+            export_mesh_request: None,
             randomize_starts_request: None,
             simulation_start_request: None,
+            export_stats_request: None,
+            export_chronicle_request: None,
+            export_timelapse_request: None,
             error_window: None,
         }
     }
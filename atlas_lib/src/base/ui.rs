@@ -16,10 +16,7 @@
 use crate::{
     base::events::EventStruct,
     domain::map::MapDataLayer,
-    ui::{
-        sidebar::{SidebarControl, SidebarEnumDropdown},
-        window,
-    },
+    ui::{window, UiEditableEnum},
 };
 
 /// Minimum camera zoom.
@@ -56,8 +53,18 @@ pub enum FileDialogMode {
     Import,
     /// Export all data.
     Export,
+    /// Export all numeric layers as GIS rasters.
+    ExportGis,
+    /// Export terrain as a 3D mesh.
+    ExportMesh,
     /// Import initial data.
     ImportSpecial,
+    /// Save recorded statistics to CSV file.
+    SaveStats,
+    /// Save the chronicle to JSON file.
+    SaveChronicle,
+    /// Save timelapse frames to a directory.
+    SaveTimelapse,
 }
 
 /// Struct that contains only the UI-related state (no logic).
@@ -81,6 +88,8 @@ pub struct UiStateBase {
     pub about_open: bool,
     /// Currently viewed map layer.
     pub current_layer: MapDataLayer,
+    /// Names of user-defined custom layers, listed in the layer dropdown.
+    pub custom_layers: Vec<String>,
     /// Currently visible map overlays.
     pub overlays: [bool; 3],
 }
@@ -97,6 +106,7 @@ fn default() -> Self {
             camera: Default::default(),
             about_open: false,
             current_layer: Default::default(),
+            custom_layers: vec![],
             overlays: [true, true, true],
         }
     }
@@ -175,6 +185,11 @@ fn handle(&mut self, ctx: &Context, ui_state: &mut UiStateBase) {
                     FileDialogMode::Import => self.import(path),
                     FileDialogMode::ImportSpecial => self.import_special(path),
                     FileDialogMode::Export => self.export(path),
+                    FileDialogMode::ExportGis => self.export_gis(path),
+                    FileDialogMode::ExportMesh => self.export_mesh(path),
+                    FileDialogMode::SaveStats => self.save_stats(path),
+                    FileDialogMode::SaveChronicle => self.save_chronicle(path),
+                    FileDialogMode::SaveTimelapse => self.save_timelapse(path),
                 };
             }
             ui_state.file_dialog = None;
@@ -189,6 +204,11 @@ fn handle(&mut self, ctx: &Context, ui_state: &mut UiStateBase) {
     fn import(&mut self, path: &Path);
     fn import_special(&mut self, path: &Path);
     fn export(&mut self, path: &Path);
+    fn export_gis(&mut self, path: &Path);
+    fn export_mesh(&mut self, path: &Path);
+    fn save_stats(&mut self, path: &Path);
+    fn save_chronicle(&mut self, path: &Path);
+    fn save_timelapse(&mut self, path: &Path);
 }
 
 /// Handler for the egui error window.
@@ -274,13 +294,9 @@ fn create_layer_view_settings(
         events: &mut EventStruct,
     ) {
         // Layer visibility dropdown.
-        // NOTE: `ui.horizontal_wrapped()` respects `ui.end_row()` used internally by a `SidebarControl`.
         ui.horizontal(|ui| {
-            let old = ui_base.current_layer;
-            let selection = SidebarEnumDropdown::new(ui, "Layer", &mut ui_base.current_layer).show(None);
-            SidebarEnumDropdown::post_show(selection, &mut ui_base.current_layer);
             // Trigger layer change event as needed.
-            if old != ui_base.current_layer {
+            if layer_dropdown(ui, ui_base) {
                 events.viewed_layer_changed = Some(ui_base.current_layer);
             }
         });
@@ -435,13 +451,51 @@ pub fn open_file_dialog(ui_base: &mut UiStateBase, mode: FileDialogMode) {
         FileDialogMode::LoadData(_) => FileDialog::open_file(None),
         FileDialogMode::Import => FileDialog::select_folder(None),
         FileDialogMode::Export => FileDialog::select_folder(None),
+        FileDialogMode::ExportGis => FileDialog::select_folder(None),
+        FileDialogMode::ExportMesh => FileDialog::select_folder(None),
         FileDialogMode::ImportSpecial => FileDialog::select_folder(None),
+        FileDialogMode::SaveStats => FileDialog::save_file(None),
+        FileDialogMode::SaveChronicle => FileDialog::save_file(None),
+        FileDialogMode::SaveTimelapse => FileDialog::select_folder(None),
     };
     file_picker.open();
     ui_base.file_dialog = Some(file_picker);
     ui_base.file_dialog_mode = mode;
 }
 
+/// Helper function
+///
+/// Show the viewed layer dropdown, with custom layers listed after the built-in ones.
+/// Returns `true` if a different layer was selected.
+pub fn layer_dropdown(ui: &mut Ui, ui_base: &mut UiStateBase) -> bool {
+    let old = ui_base.current_layer;
+    let mut selection = match old {
+        MapDataLayer::Custom(i) => MapDataLayer::LEN + i as usize,
+        _ => old.self_as_index(),
+    };
+    let names = &ui_base.custom_layers;
+    ui.label("Layer");
+    egui::ComboBox::new("Layer", "").show_index(ui, &mut selection, MapDataLayer::LEN + names.len(), |i| {
+        if i < MapDataLayer::LEN {
+            MapDataLayer::index_to_str(i).to_string()
+        } else {
+            // Invisible layers can still be viewed from their panels.
+            names
+                .get(i - MapDataLayer::LEN)
+                .cloned()
+                .unwrap_or_else(|| format!("{:?}", old))
+        }
+    });
+    ui_base.current_layer = if selection < MapDataLayer::LEN {
+        old.index_as_self(selection)
+    } else if selection < MapDataLayer::LEN + names.len() {
+        MapDataLayer::Custom((selection - MapDataLayer::LEN) as u8)
+    } else {
+        old
+    };
+    old != ui_base.current_layer
+}
+
 /// Helper function.
 ///
 /// Calculate viewport size to not overlap the sidebar.
@@ -445,9 +445,8 @@ pub fn celsius_to_byte(x: f32) -> u8 {
     (x * 2.0 + 100.0) as u8
 }
 
-#[allow(unused)]
 pub fn celsius_from_byte(x: u8) -> f32 {
-    (x - 100) as f32 / 2.0
+    (x as f32 - 100.0) / 2.0
 }
 
 pub fn celsius_to_fraction(x: f32) -> f32 {
@@ -473,7 +472,6 @@ pub fn precip_to_byte(x: f32) -> u8 {
     (x / 20.0) as u8
 }
 
-#[allow(unused)]
 pub fn precip_from_byte(x: u8) -> f32 {
     x as f32 * 20.0
 }
@@ -0,0 +1,56 @@
+use crate::{
+    bevy_egui::egui::lerp,
+    config::Error,
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Description of a user-defined custom layer, shared by the generator and the simulator.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CustomLayerInfo {
+    /// Unique layer name.
+    pub name: String,
+    /// Color of the lowest layer value.
+    pub color_low: [u8; 3],
+    /// Color of the highest layer value.
+    pub color_high: [u8; 3],
+}
+
+impl CustomLayerInfo {
+    /// Get the name of the file this layer is exported to. Name characters unsafe for paths are replaced.
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|x| {
+                if x.is_ascii_alphanumeric() {
+                    x.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("custom_{}.png", name)
+    }
+
+    /// Get the color ramp value for a layer value.
+    pub fn get_color(&self, value: u8) -> [u8; 4] {
+        let t = value as f32 / 255.0;
+        let [r, g, b] =
+            [0, 1, 2].map(|i| lerp(self.color_low[i] as f32..=self.color_high[i] as f32, t) as u8);
+        [r, g, b, 255]
+    }
+}
+
+/// Check that all custom layers are named and that no two layers are exported to the same file.
+pub fn check_custom_layers(layers: &[CustomLayerInfo]) -> Result<(), Error> {
+    for (i, info) in layers.iter().enumerate() {
+        if info.name.is_empty() {
+            return Err(Error::EmptyCustomLayerName(i));
+        }
+        let file_name = info.file_name();
+        if let Some(other) = layers[..i].iter().find(|x| x.file_name() == file_name) {
+            return Err(Error::DuplicateCustomLayer(other.name.clone(), info.name.clone()));
+        }
+    }
+    Ok(())
+}
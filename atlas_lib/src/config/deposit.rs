@@ -12,6 +12,11 @@
 /// Config for the resource deposits generation.
 #[derive(Debug, Clone, Deserialize, Resource, Serialize, MakeUi)]
 pub struct DepositsConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    #[serde(default)]
+    pub seed: u32,
     #[name("Chunk Size")]
     #[control(SidebarSlider)]
     #[add(clamp_range(1..=255))]
@@ -27,6 +32,7 @@ pub struct DepositsConfig {
 impl Default for DepositsConfig {
     fn default() -> Self {
         Self {
+            seed: 0,
             chunk_size: 18,
             chunks: Default::default(),
             types: make_default_deposits(),
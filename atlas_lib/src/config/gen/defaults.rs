@@ -9,6 +9,16 @@ fn default() -> Self {
             preview_model: Default::default(),
             generation_model: Default::default(),
             world_size: [360, 180],
+            mesh_export: Default::default(),
+        }
+    }
+}
+
+impl Default for MeshExportConfig {
+    fn default() -> Self {
+        Self {
+            exaggeration: 20.0,
+            decimation: 1,
         }
     }
 }
@@ -18,7 +28,7 @@ fn default() -> Self {
         Self {
             sea_level: 0.4,
             algorithm: Default::default(),
-            influence_shape: Default::default(),
+            influence: Default::default(),
         }
     }
 }
@@ -27,6 +37,7 @@ impl Default for TopographyConfig {
     fn default() -> Self {
         Self {
             coastal_erosion: 1,
+            erosion: Default::default(),
             algorithm: NoiseAlgorithm::Perlin(FbmConfig {
                 midpoint: QuadPointLerp {
                     start: 0.1,
@@ -39,7 +50,23 @@ fn default() -> Self {
                 },
                 ..Default::default()
             }),
-            influence_shape: Default::default(),
+            influence: Default::default(),
+        }
+    }
+}
+
+impl Default for ErosionConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            iterations: 0,
+            erosion_rate: 0.3,
+            deposition_rate: 0.3,
+            evaporation_rate: 0.02,
+            inertia: 0.05,
+            capacity: 4.0,
+            thermal_iterations: 0,
+            talus: 4.0,
         }
     }
 }
@@ -49,9 +76,11 @@ fn default() -> Self {
         Self {
             latitudinal: Default::default(),
             lapse_rate: 5.0,
+            continentality: Default::default(),
+            currents: Default::default(),
             algorithm_strength: 0.1,
             algorithm: Default::default(),
-            influence_shape: Default::default(),
+            influence: Default::default(),
         }
     }
 }
@@ -73,15 +102,83 @@ fn default() -> Self {
     }
 }
 
+impl Default for ContinentalityConfig {
+    fn default() -> Self {
+        Self {
+            range: 15,
+            equator_shift: 2.0,
+            pole_shift: -10.0,
+        }
+    }
+}
+
+impl Default for OceanCurrentsConfig {
+    fn default() -> Self {
+        Self {
+            range: 8,
+            warm_shift: 4.0,
+            cold_shift: 4.0,
+        }
+    }
+}
+
 impl Default for PrecipitationConfig {
     fn default() -> Self {
         Self {
             latitudinal: Default::default(),
             amp_point: 2000.0,
             drop_per_height: 1.5,
+            rain_shadow: Default::default(),
             algorithm_strength: 0.1,
             algorithm: Default::default(),
-            influence_shape: Default::default(),
+            influence: Default::default(),
+        }
+    }
+}
+
+impl Default for RainShadowConfig {
+    fn default() -> Self {
+        Self {
+            strength: 0.5,
+            uplift_loss: 0.05,
+            land_loss: 0.01,
+            sea_gain: 0.2,
+            windward_boost: 2.0,
+            winds: Default::default(),
+        }
+    }
+}
+
+impl Default for WindBandsConfig {
+    fn default() -> Self {
+        Self {
+            tropical_limit: 30.0,
+            polar_limit: 60.0,
+            tropical_wind: -1.0,
+            temperate_wind: 1.0,
+            polar_wind: -1.0,
+        }
+    }
+}
+
+impl Default for RiversConfig {
+    fn default() -> Self {
+        Self {
+            discharge_threshold: 40.0,
+            lakes: true,
+            min_lake_depth: 2,
+        }
+    }
+}
+
+impl Default for CustomLayerConfig {
+    fn default() -> Self {
+        Self {
+            name: "Custom".into(),
+            color_low: [0, 0, 0],
+            color_high: [255, 255, 255],
+            algorithm: Default::default(),
+            influence: Default::default(),
         }
     }
 }
@@ -122,6 +219,62 @@ fn default() -> Self {
                 ..Default::default()
             },
             offset: Default::default(),
+            warp: Default::default(),
+        }
+    }
+}
+
+impl Default for DomainWarpConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 0,
+            strength: 0.3,
+            frequency: 2.0,
+        }
+    }
+}
+
+impl Default for WorleyConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            frequency: 8.0,
+            distance: Default::default(),
+            return_type: Default::default(),
+            bias: 0.0,
+            midpoint: FbmConfig::default().midpoint,
+            offset: Default::default(),
+        }
+    }
+}
+
+impl Default for RidgedConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            detail: 6,
+            frequency: 3.0,
+            neatness: 2.0,
+            roughness: 1.0,
+            attenuation: 2.0,
+            bias: 0.0,
+            midpoint: FbmConfig::default().midpoint,
+            offset: Default::default(),
+        }
+    }
+}
+
+impl Default for BillowConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            detail: 6,
+            frequency: 3.0,
+            neatness: 2.0,
+            roughness: 0.5,
+            bias: 0.0,
+            midpoint: FbmConfig::default().midpoint,
+            offset: Default::default(),
         }
     }
 }
@@ -132,6 +285,21 @@ fn default() -> Self {
     }
 }
 
+impl Default for TectonicConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            plates: 12,
+            continental_ratio: 0.4,
+            boundary_width: 8.0,
+            uplift: 0.4,
+            rift_depth: 0.2,
+            warp: 0.3,
+            roughness: 0.1,
+        }
+    }
+}
+
 impl Default for QuadPointLerp {
     fn default() -> Self {
         Self {
@@ -180,6 +348,51 @@ fn default() -> Self {
     }
 }
 
+impl Default for InfluencePolygonConfig {
+    fn default() -> Self {
+        Self {
+            influence_mode: Default::default(),
+            influence_strength: 1.0,
+            falloff: 20,
+            offset: Default::default(),
+            midpoint: Default::default(),
+            num_vertices: 4,
+            vertices: [[-50, -50], [50, -50], [50, 50], [-50, 50]]
+                .into_iter()
+                .map(|position| InfluencePolygonVertex { position })
+                .collect(),
+        }
+    }
+}
+
+impl Default for InfluenceSplineConfig {
+    fn default() -> Self {
+        Self {
+            influence_mode: Default::default(),
+            influence_strength: 1.0,
+            offset: Default::default(),
+            midpoint: Default::default(),
+            num_points: 3,
+            points: [[-100, 0], [0, -50], [100, 0]]
+                .into_iter()
+                .map(|position| InfluenceSplinePoint {
+                    position,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for InfluenceSplinePoint {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            thickness: 20,
+        }
+    }
+}
+
 impl Default for InfluenceFbmConfig {
     fn default() -> Self {
         Self {
@@ -9,16 +9,24 @@
             BiomeConfig, ClimateConfig, ALTITUDE_MAX, ALTITUDE_MIN, CELSIUS_MAX, CELSIUS_MIN, PRECIP_MAX,
             PRECIP_MIN,
         },
+        custom::CustomLayerInfo,
         deposit::DepositsConfig,
         sim::AtlasSimConfig,
         AtlasConfig, ClimatePreviewMode, IntoSimConfig, WorldModel, MAX_WORLD_SIZE,
     },
+    domain::map::MAX_CUSTOM_LAYERS,
     serde_derive::{Deserialize, Serialize},
     ui::{sidebar::*, UiEditableEnum},
     MakeUi, MakeUiEnum, UiEditableEnum,
 };
 
 pub const CONFIG_NAME: &str = "atlasgen.toml";
+/// Max number of influence shapes per layer.
+pub const MAX_INFLUENCE_SHAPES: u32 = 16;
+/// Max number of polygon vertices or spline points in an influence shape.
+pub const MAX_INFLUENCE_POINTS: u32 = 64;
+/// Max number of tiles merged into one terrain mesh quad (along each axis).
+pub const MAX_MESH_DECIMATION: u32 = 64;
 
 /// Complete configuration for the map generator.
 #[derive(Debug, Default, Deserialize, Resource, Serialize)]
@@ -28,8 +36,12 @@ pub struct AtlasGenConfig {
     pub topography: TopographyConfig,
     pub temperature: TemperatureConfig,
     pub precipitation: PrecipitationConfig,
+    #[serde(default)]
+    pub rivers: RiversConfig,
     pub climate: ClimateConfig,
     pub deposits: DepositsConfig,
+    #[serde(default)]
+    pub custom: CustomLayersConfig,
 }
 
 impl AtlasConfig for AtlasGenConfig {
@@ -37,6 +49,10 @@ fn get_world_size(&self) -> (u32, u32) {
         (self.general.world_size[0], self.general.world_size[1])
     }
 
+    fn get_world_model(&self) -> WorldModel {
+        self.general.generation_model
+    }
+
     fn get_preview_model(&self) -> WorldModel {
         self.general.preview_model
     }
@@ -54,6 +70,10 @@ fn get_biome(&self, i: u8) -> &BiomeConfig {
             &self.climate.biomes[i]
         }
     }
+
+    fn get_custom_layers(&self) -> Vec<CustomLayerInfo> {
+        self.custom.get_layers().iter().map(|x| x.get_info()).collect()
+    }
 }
 
 impl AtlasGenConfig {
@@ -61,11 +81,13 @@ pub fn into_sim_config(&self) -> AtlasSimConfig {
         AtlasSimConfig {
             general: crate::config::sim::GeneralConfig {
                 world_size: self.general.world_size,
+                world_model: self.general.generation_model,
             },
             scenario: Default::default(),
             climate: self.climate.into_sim_config(),
             rules: Default::default(),
             deposits: self.deposits.clone(),
+            custom_layers: self.get_custom_layers(),
         }
     }
 }
@@ -88,16 +110,33 @@ pub struct GeneralConfig {
     #[name("World Model Preview")]
     #[control(SidebarEnumDropdown)]
     pub preview_model: WorldModel,
-    /* NOTE: Disabled. Doesn't work with the generator.
     #[name("World Model Generation")]
     #[control(SidebarEnumDropdown)]
-    */
-    #[serde(skip)]
+    #[serde(default)]
     pub generation_model: WorldModel,
     #[name("World Size")]
     #[control(SidebarSliderN)]
     #[add(clamp_range(1..=MAX_WORLD_SIZE))]
     pub world_size: [u32; 2],
+    #[name("Terrain Mesh Export")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub mesh_export: MeshExportConfig,
+}
+
+/// Config for the terrain mesh export.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct MeshExportConfig {
+    #[name("Vertical Exaggeration")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1000.0))]
+    #[add(speed(0.5))]
+    pub exaggeration: f32,
+    #[name("Decimation (Tiles per Quad)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=MAX_MESH_DECIMATION))]
+    #[add(speed(0.1))]
+    pub decimation: u32,
 }
 
 /// How map should be colored in the map preview.
@@ -123,14 +162,15 @@ pub struct ContinentsConfig {
     #[name("Noise Algorithm")]
     #[control(SidebarEnumSection)]
     pub algorithm: NoiseAlgorithm,
-    #[name("Influence Shape")]
-    #[control(SidebarEnumSection)]
-    pub influence_shape: InfluenceShape,
+    #[name("Influence")]
+    #[control(SidebarStructSection)]
+    #[serde(default, alias = "influence_shape")]
+    pub influence: InfluenceConfig,
 }
 
-impl AsRef<InfluenceShape> for ContinentsConfig {
-    fn as_ref(&self) -> &InfluenceShape {
-        &self.influence_shape
+impl AsRef<InfluenceConfig> for ContinentsConfig {
+    fn as_ref(&self) -> &InfluenceConfig {
+        &self.influence
     }
 }
 
@@ -148,17 +188,71 @@ pub struct TopographyConfig {
     #[add(clamp_range(0..=20))]
     #[add(speed(0.5))]
     pub coastal_erosion: u8,
+    #[name("Erosion")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub erosion: ErosionConfig,
     #[name("Noise Algorithm")]
     #[control(SidebarEnumSection)]
     pub algorithm: NoiseAlgorithm,
-    #[name("Influence Shape")]
-    #[control(SidebarEnumSection)]
-    pub influence_shape: InfluenceShape,
+    #[name("Influence")]
+    #[control(SidebarStructSection)]
+    #[serde(default, alias = "influence_shape")]
+    pub influence: InfluenceConfig,
+}
+
+/// Config for the hydraulic and thermal erosion of topography.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct ErosionConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    pub seed: u32,
+    #[name("Hydraulic Iterations (Droplets)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=1000000))]
+    #[add(speed(1000.0))]
+    pub iterations: u32,
+    #[name("Erosion Rate")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub erosion_rate: f32,
+    #[name("Deposition Rate")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub deposition_rate: f32,
+    #[name("Evaporation Rate")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.01))]
+    pub evaporation_rate: f32,
+    #[name("Droplet Inertia")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.01))]
+    pub inertia: f32,
+    #[name("Sediment Capacity")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.1..=32.0))]
+    #[add(speed(0.1))]
+    pub capacity: f32,
+    #[name("Thermal Iterations")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=100))]
+    #[add(speed(0.5))]
+    pub thermal_iterations: u32,
+    #[name("Thermal Talus Threshold")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=255.0))]
+    #[add(speed(0.5))]
+    pub talus: f32,
 }
 
-impl AsRef<InfluenceShape> for TopographyConfig {
-    fn as_ref(&self) -> &InfluenceShape {
-        &self.influence_shape
+impl AsRef<InfluenceConfig> for TopographyConfig {
+    fn as_ref(&self) -> &InfluenceConfig {
+        &self.influence
     }
 }
 
@@ -176,6 +270,14 @@ pub struct TemperatureConfig {
     #[add(clamp_range(0.0..=10.0))]
     #[add(speed(0.1))]
     pub lapse_rate: f32,
+    #[name("Continentality")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub continentality: ContinentalityConfig,
+    #[name("Ocean Currents")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub currents: OceanCurrentsConfig,
     #[name("Latitudinal Settings [C]")]
     #[control(SidebarStructSection)]
     pub latitudinal: LatitudinalTemperatureLerp,
@@ -187,14 +289,15 @@ pub struct TemperatureConfig {
     #[name("Noise Algorithm")]
     #[control(SidebarEnumSection)]
     pub algorithm: NoiseAlgorithm,
-    #[name("Influence Shape")]
-    #[control(SidebarEnumSection)]
-    pub influence_shape: InfluenceShape,
+    #[name("Influence")]
+    #[control(SidebarStructSection)]
+    #[serde(default, alias = "influence_shape")]
+    pub influence: InfluenceConfig,
 }
 
-impl AsRef<InfluenceShape> for TemperatureConfig {
-    fn as_ref(&self) -> &InfluenceShape {
-        &self.influence_shape
+impl AsRef<InfluenceConfig> for TemperatureConfig {
+    fn as_ref(&self) -> &InfluenceConfig {
+        &self.influence
     }
 }
 
@@ -204,6 +307,47 @@ fn as_ref(&self) -> &NoiseAlgorithm {
     }
 }
 
+/// Config for the temperature shift of tiles far away from the sea.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct ContinentalityConfig {
+    #[name("Range [tiles]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=100))]
+    #[add(speed(0.5))]
+    pub range: u32,
+    #[name("Interior Shift at Equator [C]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-20.0..=20.0))]
+    #[add(speed(0.1))]
+    pub equator_shift: f32,
+    #[name("Interior Shift at Poles [C]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-20.0..=20.0))]
+    #[add(speed(0.1))]
+    pub pole_shift: f32,
+}
+
+/// Config for the temperature shift of coasts by warm and cold ocean currents.
+/// Currents follow prevailing winds from precipitation settings.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct OceanCurrentsConfig {
+    #[name("Coastal Range [tiles]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=50))]
+    #[add(speed(0.5))]
+    pub range: u32,
+    #[name("Warm Current Shift [C]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=20.0))]
+    #[add(speed(0.1))]
+    pub warm_shift: f32,
+    #[name("Cold Current Shift [C]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=20.0))]
+    #[add(speed(0.1))]
+    pub cold_shift: f32,
+}
+
 /// Config for the precipitation generation.
 #[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
 pub struct PrecipitationConfig {
@@ -216,6 +360,10 @@ pub struct PrecipitationConfig {
     #[add(clamp_range(0.0..=10.0))]
     #[add(speed(0.1))]
     pub drop_per_height: f32,
+    #[name("Rain Shadow")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub rain_shadow: RainShadowConfig,
     #[name("Latitudinal Settings [mm]")]
     #[control(SidebarStructSection)]
     pub latitudinal: LatitudinalPrecipitationLerp,
@@ -227,14 +375,15 @@ pub struct PrecipitationConfig {
     #[name("Noise Algorithm")]
     #[control(SidebarEnumSection)]
     pub algorithm: NoiseAlgorithm,
-    #[name("Influence Map Type")]
-    #[control(SidebarEnumSection)]
-    pub influence_shape: InfluenceShape,
+    #[name("Influence")]
+    #[control(SidebarStructSection)]
+    #[serde(default, alias = "influence_shape")]
+    pub influence: InfluenceConfig,
 }
 
-impl AsRef<InfluenceShape> for PrecipitationConfig {
-    fn as_ref(&self) -> &InfluenceShape {
-        &self.influence_shape
+impl AsRef<InfluenceConfig> for PrecipitationConfig {
+    fn as_ref(&self) -> &InfluenceConfig {
+        &self.influence
     }
 }
 
@@ -244,6 +393,177 @@ fn as_ref(&self) -> &NoiseAlgorithm {
     }
 }
 
+/// Config for the moisture transport by prevailing winds.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct RainShadowConfig {
+    #[name("Strength")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub strength: f32,
+    #[name("Moisture Loss per Height Unit")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.01))]
+    pub uplift_loss: f32,
+    #[name("Moisture Loss over Land")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.01))]
+    pub land_loss: f32,
+    #[name("Moisture Gain over Sea")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.01))]
+    pub sea_gain: f32,
+    #[name("Windward Boost")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=10.0))]
+    #[add(speed(0.1))]
+    pub windward_boost: f32,
+    #[name("Prevailing Winds")]
+    #[control(SidebarStructSection)]
+    pub winds: WindBandsConfig,
+}
+
+/// Prevailing east-west winds in three latitudinal bands, mirrored between hemispheres.
+/// Positive values blow eastwards, negative values blow westwards.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct WindBandsConfig {
+    #[name("Tropical Band Limit [deg]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=90.0))]
+    pub tropical_limit: f32,
+    #[name("Polar Band Limit [deg]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=90.0))]
+    pub polar_limit: f32,
+    #[name("Tropical Wind")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-1.0..=1.0))]
+    #[add(speed(0.1))]
+    pub tropical_wind: f32,
+    #[name("Temperate Wind")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-1.0..=1.0))]
+    #[add(speed(0.1))]
+    pub temperate_wind: f32,
+    #[name("Polar Wind")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-1.0..=1.0))]
+    #[add(speed(0.1))]
+    pub polar_wind: f32,
+}
+
+impl WindBandsConfig {
+    /// Get wind at a latitude (in degrees).
+    pub fn wind_at(&self, latitude: f32) -> f32 {
+        let latitude = latitude.abs();
+        if latitude < self.tropical_limit {
+            self.tropical_wind
+        } else if latitude < self.polar_limit {
+            self.temperate_wind
+        } else {
+            self.polar_wind
+        }
+    }
+}
+
+/// Config for the river and lake generation.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct RiversConfig {
+    #[name("Discharge Threshold")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1.0..=10000.0))]
+    #[add(speed(1.0))]
+    pub discharge_threshold: f32,
+    #[name("Fill Lakes")]
+    #[control(SidebarCheckbox)]
+    pub lakes: bool,
+    #[name("Minimum Lake Depth")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=50))]
+    #[add(speed(0.5))]
+    pub min_lake_depth: u8,
+}
+
+/// Config for the user-defined custom layers.
+#[derive(Debug, Default, Deserialize, Resource, Serialize, MakeUi)]
+pub struct CustomLayersConfig {
+    #[name("# of Custom Layers")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=MAX_CUSTOM_LAYERS as u32))]
+    pub num_layers: u32,
+    #[name("Custom Layers")]
+    #[control(SidebarStructList)]
+    pub layers: Vec<CustomLayerConfig>,
+}
+
+impl CustomLayersConfig {
+    /// Add or remove layers (and their influence shapes) to match their requested number.
+    pub fn ensure_layers(&mut self) {
+        self.layers
+            .resize_with(self.num_layers as usize, Default::default);
+        for layer in &mut self.layers {
+            layer.influence.ensure_shapes();
+        }
+    }
+
+    /// Get all declared layers, up to the requested number and the max number of layer slots.
+    pub fn get_layers(&self) -> &[CustomLayerConfig] {
+        let len = self
+            .layers
+            .len()
+            .min(self.num_layers as usize)
+            .min(MAX_CUSTOM_LAYERS);
+        &self.layers[..len]
+    }
+}
+
+/// A single user-defined layer, generated from noise and influence shapes, or loaded from an image.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct CustomLayerConfig {
+    #[name("Name")]
+    #[control(SidebarTextbox)]
+    pub name: String,
+    #[name("Color (Lowest Value)")]
+    #[control(SidebarColor)]
+    pub color_low: [u8; 3],
+    #[name("Color (Highest Value)")]
+    #[control(SidebarColor)]
+    pub color_high: [u8; 3],
+    #[name("Noise Algorithm")]
+    #[control(SidebarEnumSection)]
+    pub algorithm: NoiseAlgorithm,
+    #[name("Influence")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub influence: InfluenceConfig,
+}
+
+impl CustomLayerConfig {
+    /// Get the layer description shared with the simulator.
+    pub fn get_info(&self) -> CustomLayerInfo {
+        CustomLayerInfo {
+            name: self.name.clone(),
+            color_low: self.color_low,
+            color_high: self.color_high,
+        }
+    }
+}
+
+impl AsRef<InfluenceConfig> for CustomLayerConfig {
+    fn as_ref(&self) -> &InfluenceConfig {
+        &self.influence
+    }
+}
+
+impl AsRef<NoiseAlgorithm> for CustomLayerConfig {
+    fn as_ref(&self) -> &NoiseAlgorithm {
+        &self.algorithm
+    }
+}
+
 /// Specialised multi-segment lerp operating on latitude coordinates.
 /// HACK: Different type for temperature and precipitation, because clamp limits are different.
 #[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
@@ -369,11 +689,216 @@ pub struct FbmConfig {
     #[name("Offset")]
     #[control(SidebarSliderN)]
     pub offset: [f32; 2],
+    #[name("Domain Warping")]
+    #[control(SidebarStructSection)]
+    #[serde(default)]
+    pub warp: DomainWarpConfig,
+    #[name("Quad Point Interpolation")]
+    #[control(SidebarStructSection)]
+    pub midpoint: QuadPointLerp,
+}
+
+/// Displacement of noise sample coordinates by a secondary noise field.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct DomainWarpConfig {
+    #[name("Iterations")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=5))]
+    #[add(speed(0.5))]
+    pub iterations: u8,
+    #[name("Strength")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=2.0))]
+    #[add(speed(0.05))]
+    pub strength: f32,
+    #[name("Scale (Frequency)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.1..=10.0))]
+    #[add(speed(0.1))]
+    pub frequency: f32,
+}
+
+/// Worley (cellular) noise sampling parameters.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct WorleyConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    pub seed: u32,
+    #[name("Scale (Frequency)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.1..=50.0))]
+    #[add(speed(0.1))]
+    pub frequency: f32,
+    #[name("Distance Function")]
+    #[control(SidebarEnumDropdown)]
+    pub distance: WorleyDistance,
+    #[name("Return Type")]
+    #[control(SidebarEnumDropdown)]
+    pub return_type: WorleyReturnType,
+    #[name("Bias")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-1.0..=1.0))]
+    #[add(speed(0.1))]
+    pub bias: f32,
+    #[name("Offset")]
+    #[control(SidebarSliderN)]
+    pub offset: [f32; 2],
+    #[name("Quad Point Interpolation")]
+    #[control(SidebarStructSection)]
+    pub midpoint: QuadPointLerp,
+}
+
+/// How distance to Worley noise cell centers is measured.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Resource, Serialize, UiEditableEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WorleyDistance {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+/// What value Worley noise returns for a point.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Resource, Serialize, UiEditableEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WorleyReturnType {
+    /// Distance to the closest cell center.
+    #[default]
+    Distance,
+    /// Random value of the closest cell.
+    Value,
+}
+
+/// Ridged multifractal noise sampling parameters.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct RidgedConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    pub seed: u32,
+    #[name("Detail (Octaves)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=12))]
+    #[add(speed(0.5))]
+    pub detail: u8,
+    #[name("Scale (Frequency)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.1..=10.0))]
+    #[add(speed(0.1))]
+    pub frequency: f32,
+    #[name("Neatness (Lacunarity)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1.0..=10.0))]
+    #[add(speed(0.1))]
+    pub neatness: f32,
+    #[name("Roughness (Persistance)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub roughness: f32,
+    #[name("Attenuation")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.1..=10.0))]
+    #[add(speed(0.1))]
+    pub attenuation: f32,
+    #[name("Bias")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-1.0..=1.0))]
+    #[add(speed(0.1))]
+    pub bias: f32,
+    #[name("Offset")]
+    #[control(SidebarSliderN)]
+    pub offset: [f32; 2],
     #[name("Quad Point Interpolation")]
     #[control(SidebarStructSection)]
     pub midpoint: QuadPointLerp,
 }
 
+/// Billow noise sampling parameters.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct BillowConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    pub seed: u32,
+    #[name("Detail (Octaves)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=12))]
+    #[add(speed(0.5))]
+    pub detail: u8,
+    #[name("Scale (Frequency)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.1..=10.0))]
+    #[add(speed(0.1))]
+    pub frequency: f32,
+    #[name("Neatness (Lacunarity)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1.0..=10.0))]
+    #[add(speed(0.1))]
+    pub neatness: f32,
+    #[name("Roughness (Persistance)")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub roughness: f32,
+    #[name("Bias")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(-1.0..=1.0))]
+    #[add(speed(0.1))]
+    pub bias: f32,
+    #[name("Offset")]
+    #[control(SidebarSliderN)]
+    pub offset: [f32; 2],
+    #[name("Quad Point Interpolation")]
+    #[control(SidebarStructSection)]
+    pub midpoint: QuadPointLerp,
+}
+
+/// Tectonic plates sampling parameters.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct TectonicConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    pub seed: u32,
+    #[name("Number of Plates")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(2..=64))]
+    #[add(speed(0.5))]
+    pub plates: u32,
+    #[name("Continental Plate Ratio")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub continental_ratio: f32,
+    #[name("Boundary Width [tiles]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1.0..=100.0))]
+    #[add(speed(0.5))]
+    pub boundary_width: f32,
+    #[name("Convergent Uplift")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub uplift: f32,
+    #[name("Divergent Rift Depth")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub rift_depth: f32,
+    #[name("Boundary Irregularity")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub warp: f32,
+    #[name("Roughness")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub roughness: f32,
+}
+
 /// Algorithm describes the noise algorithm that should be used to generate a layer,
 /// as well as its paramateres.
 #[derive(Clone, Debug, Deserialize, Resource, Serialize, MakeUiEnum, UiEditableEnum)]
@@ -383,6 +908,10 @@ pub enum NoiseAlgorithm {
     PerlinSurflet(FbmConfig),
     OpenSimplex(FbmConfig),
     SuperSimplex(FbmConfig),
+    Worley(WorleyConfig),
+    RidgedMulti(RidgedConfig),
+    Billow(BillowConfig),
+    Tectonic(TectonicConfig),
     #[empty]
     FromImage,
 }
@@ -459,6 +988,97 @@ pub fn lerp(&self, x: f32) -> f32 {
     }
 }
 
+/// Influence map settings. Every shape is generated separately,
+/// and the results are combined into one influence map.
+#[derive(Debug, Default, Deserialize, Resource, Serialize, MakeUi)]
+#[serde(from = "InfluenceConfigFile")]
+pub struct InfluenceConfig {
+    #[name("Influence Compositing")]
+    #[control(SidebarEnumDropdown)]
+    pub compositing: InfluenceCompositing,
+    #[name("# of Influence Shapes")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=MAX_INFLUENCE_SHAPES))]
+    pub num_shapes: u32,
+    #[name("Influence Shapes")]
+    #[control(SidebarStructList)]
+    pub shapes: Vec<InfluenceShapeConfig>,
+}
+
+impl InfluenceConfig {
+    /// Add or remove shapes (and their points) to match their requested number.
+    pub fn ensure_shapes(&mut self) {
+        self.shapes
+            .resize_with(self.num_shapes as usize, Default::default);
+        for shape in &mut self.shapes {
+            shape.shape.ensure_points();
+        }
+    }
+
+    /// Check if any shape affects the influence map.
+    pub fn is_used(&self) -> bool {
+        self.shapes
+            .iter()
+            .any(|x| !matches!(x.shape, InfluenceShape::None))
+    }
+}
+
+/// Influence map settings as stored in a config file.
+/// Configs written before multiple shapes were supported have a single `influence_shape` instead.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InfluenceConfigFile {
+    Shapes {
+        compositing: InfluenceCompositing,
+        num_shapes: u32,
+        shapes: Vec<InfluenceShapeConfig>,
+    },
+    Single(InfluenceShape),
+}
+
+impl From<InfluenceConfigFile> for InfluenceConfig {
+    fn from(value: InfluenceConfigFile) -> Self {
+        match value {
+            InfluenceConfigFile::Shapes {
+                compositing,
+                num_shapes,
+                shapes,
+            } => Self {
+                compositing,
+                num_shapes,
+                shapes,
+            },
+            InfluenceConfigFile::Single(InfluenceShape::None) => Self::default(),
+            InfluenceConfigFile::Single(shape) => Self {
+                compositing: Default::default(),
+                num_shapes: 1,
+                shapes: vec![InfluenceShapeConfig { shape }],
+            },
+        }
+    }
+}
+
+/// A single influence shape in the list.
+#[derive(Debug, Default, Deserialize, Resource, Serialize, MakeUi)]
+pub struct InfluenceShapeConfig {
+    #[name("Influence Shape")]
+    #[control(SidebarEnumSection)]
+    pub shape: InfluenceShape,
+}
+
+/// How values of multiple influence shapes should be combined.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Resource, Serialize, UiEditableEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum InfluenceCompositing {
+    /// Strongest upwards scaling wins.
+    #[default]
+    Max,
+    /// Scaling of all shapes is summed up.
+    Add,
+    /// Scaling of all shapes is multiplied.
+    Multiply,
+}
+
 /// What shape should be generated for the influence map.
 #[derive(Debug, Deserialize, Resource, Serialize, MakeUiEnum, UiEditableEnum)]
 #[serde(rename_all = "lowercase")]
@@ -467,10 +1087,36 @@ pub enum InfluenceShape {
     None,
     Circle(InfluenceCircleConfig),
     Strip(InfluenceStripConfig),
+    Polygon(InfluencePolygonConfig),
+    Spline(InfluenceSplineConfig),
     Fbm(InfluenceFbmConfig),
     FromImage(InfluenceImageConfig),
 }
 
+impl InfluenceShape {
+    /// Add or remove polygon vertices or spline points to match their requested number.
+    pub fn ensure_points(&mut self) {
+        match self {
+            InfluenceShape::Polygon(x) => x.vertices.resize_with(x.num_vertices as usize, Default::default),
+            InfluenceShape::Spline(x) => x.points.resize_with(x.num_points as usize, Default::default),
+            _ => {}
+        }
+    }
+
+    /// Get influence mode and strength of this shape, if it has any.
+    pub fn get_mode_strength(&self) -> Option<(InfluenceMode, f32)> {
+        match self {
+            InfluenceShape::None => None,
+            InfluenceShape::Circle(x) => Some((x.influence_mode, x.influence_strength)),
+            InfluenceShape::Strip(x) => Some((x.influence_mode, x.influence_strength)),
+            InfluenceShape::Polygon(x) => Some((x.influence_mode, x.influence_strength)),
+            InfluenceShape::Spline(x) => Some((x.influence_mode, x.influence_strength)),
+            InfluenceShape::Fbm(x) => Some((x.influence_mode, x.influence_strength)),
+            InfluenceShape::FromImage(x) => Some((x.influence_mode, x.influence_strength)),
+        }
+    }
+}
+
 /// How influence values should affect data values.
 #[derive(Clone, Copy, Debug, Default, Deserialize, Resource, Serialize, UiEditableEnum)]
 #[serde(rename_all = "lowercase")]
@@ -548,6 +1194,86 @@ pub struct InfluenceStripConfig {
     pub midpoint: QuadPointLerp,
 }
 
+/// A closed polygon defined by a list of vertices (relative to offset from center).
+/// Value falloff is centered on polygon edges and controlled by "midpoint" settings.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct InfluencePolygonConfig {
+    #[name("Influence Mode")]
+    #[control(SidebarEnumDropdown)]
+    pub influence_mode: InfluenceMode,
+    #[name("Influence Strength")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub influence_strength: f32,
+    #[name("Falloff Distance")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=MAX_WORLD_SIZE))]
+    #[add(speed(10.0))]
+    pub falloff: u32,
+    #[name("Offset")]
+    #[control(SidebarSliderN)]
+    pub offset: [i32; 2],
+    #[name("Quad Point Interpolation")]
+    #[control(SidebarStructSection)]
+    pub midpoint: QuadPointLerp,
+    #[name("# of Vertices")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(3..=MAX_INFLUENCE_POINTS))]
+    pub num_vertices: u32,
+    #[name("Vertices")]
+    #[control(SidebarStructList)]
+    pub vertices: Vec<InfluencePolygonVertex>,
+}
+
+/// A single polygon vertex.
+#[derive(Debug, Default, Deserialize, Resource, Serialize, MakeUi)]
+pub struct InfluencePolygonVertex {
+    #[name("Position")]
+    #[control(SidebarSliderN)]
+    pub position: [i32; 2],
+}
+
+/// A curved band going smoothly through a list of points (relative to offset from center),
+/// with thickness interpolated between the points. Value falloff from the curve is controlled by "midpoint" settings.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct InfluenceSplineConfig {
+    #[name("Influence Mode")]
+    #[control(SidebarEnumDropdown)]
+    pub influence_mode: InfluenceMode,
+    #[name("Influence Strength")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0.0..=1.0))]
+    #[add(speed(0.1))]
+    pub influence_strength: f32,
+    #[name("Offset")]
+    #[control(SidebarSliderN)]
+    pub offset: [i32; 2],
+    #[name("Quad Point Interpolation")]
+    #[control(SidebarStructSection)]
+    pub midpoint: QuadPointLerp,
+    #[name("# of Points")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(2..=MAX_INFLUENCE_POINTS))]
+    pub num_points: u32,
+    #[name("Points")]
+    #[control(SidebarStructList)]
+    pub points: Vec<InfluenceSplinePoint>,
+}
+
+/// A single spline point, with band thickness at that point.
+#[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
+pub struct InfluenceSplinePoint {
+    #[name("Position")]
+    #[control(SidebarSliderN)]
+    pub position: [i32; 2],
+    #[name("Thickness")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(1..=MAX_WORLD_SIZE))]
+    #[add(speed(10.0))]
+    pub thickness: u32,
+}
+
 /// Data from fBm noise sampling.
 #[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
 pub struct InfluenceFbmConfig {
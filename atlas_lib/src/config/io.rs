@@ -1,9 +1,10 @@
 use crate::{
     png::{BitDepth, ColorType, Decoder, DecodingError, Encoder, EncodingError, SrgbRenderingIntent},
-    thiserror, toml,
+    serde_json, thiserror, toml,
 };
 use std::{
     fs::{self, File},
+    io::{BufReader, BufWriter, Write},
     path::Path,
 };
 
@@ -18,6 +19,8 @@ pub enum Error {
     #[error("{0}")]
     Serde(#[from] toml::ser::Error),
     #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
     PngDecode(#[from] DecodingError),
     #[error("{0}")]
     PngEncode(#[from] EncodingError),
@@ -25,12 +28,16 @@ pub enum Error {
     ResolutionMismatch(u32, u32, u32, u32),
     #[error("Image byte per pixel value is {0}, but only 4 is accepted")]
     InvalidBytePerPixel(usize),
-    #[error("Image byte per pixel value is {0}, but only 1 is accepted")]
+    #[error("Image byte per pixel value is {0}, but only 1 or 2 is accepted")]
     InvalidBytePerPixelGrey(usize),
     #[error("Image color type is not greyscale")]
     InvalidColorTypeGrey(ColorType),
     #[error("Image color type is not RGBA")]
     InvalidColorTypeRgba(ColorType),
+    #[error("Custom layer #{0} has no name")]
+    EmptyCustomLayerName(usize),
+    #[error("Custom layers \"{0}\" and \"{1}\" would be exported to the same file")]
+    DuplicateCustomLayer(String, String),
 }
 
 /// Load a generator config from a TOML file.
@@ -47,6 +54,20 @@ pub fn save_config<T: serde::Serialize>(config: &T, path: impl AsRef<Path>) -> R
     Ok(())
 }
 
+/// Load any data from a JSON file.
+pub fn load_json<T: for<'de> serde::Deserialize<'de>>(path: impl AsRef<Path>) -> Result<T> {
+    let reader = BufReader::new(File::open(path)?);
+    let data = serde_json::from_reader(reader)?;
+    Ok(data)
+}
+
+/// Save any data to a JSON file.
+pub fn save_json<T: serde::Serialize>(data: &T, path: impl AsRef<Path>) -> Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(writer, data)?;
+    Ok(())
+}
+
 /// Load a generator image (layer) from a PNG file.
 pub fn load_image(path: impl AsRef<Path>, width: u32, height: u32) -> Result<Vec<u8>> {
     let decoder = Decoder::new(File::open(path)?);
@@ -73,8 +94,14 @@ pub fn load_image(path: impl AsRef<Path>, width: u32, height: u32) -> Result<Vec
     Ok(buf)
 }
 
-/// Load layer data from a greyscale PNG file.
+/// Load layer data from an 8-bit or 16-bit greyscale PNG file.
 pub fn load_image_grey(path: impl AsRef<Path>, width: u32, height: u32) -> Result<Vec<u8>> {
+    let data = load_image_grey_precise(path, width, height)?;
+    Ok(data.iter().map(|x| x.round() as u8).collect())
+}
+
+/// Load high precision layer data (in 0.0..=255.0 range) from an 8-bit or 16-bit greyscale PNG file.
+pub fn load_image_grey_precise(path: impl AsRef<Path>, width: u32, height: u32) -> Result<Vec<f32>> {
     let decoder = Decoder::new(File::open(path)?);
     let mut reader = decoder.read_info()?;
     let info = reader.info();
@@ -84,7 +111,7 @@ pub fn load_image_grey(path: impl AsRef<Path>, width: u32, height: u32) -> Resul
     }
 
     let bypp = info.bytes_per_pixel();
-    if bypp != 1 {
+    if bypp != 1 && bypp != 2 {
         return Err(Error::InvalidBytePerPixelGrey(bypp));
     }
 
@@ -96,7 +123,15 @@ pub fn load_image_grey(path: impl AsRef<Path>, width: u32, height: u32) -> Resul
     let mut buf = vec![0; reader.output_buffer_size()];
     reader.next_frame(&mut buf)?;
 
-    Ok(buf)
+    // 16-bit samples are big endian.
+    let data = match bypp {
+        1 => buf.iter().map(|x| *x as f32).collect(),
+        _ => buf
+            .chunks_exact(2)
+            .map(|x| u16::from_be_bytes([x[0], x[1]]) as f32 / 257.0)
+            .collect(),
+    };
+    Ok(data)
 }
 
 /// Save a generator image (layer) to a PNG file.
@@ -111,6 +146,27 @@ pub fn save_image(path: impl AsRef<Path>, data: &[u8], width: u32, height: u32)
     Ok(())
 }
 
+/// Save a sequence of RGBA images as an animated PNG file. Each frame is shown for `delay` milliseconds.
+pub fn save_animation(
+    path: impl AsRef<Path>,
+    frames: &[Vec<u8>],
+    width: u32,
+    height: u32,
+    delay: u16,
+) -> Result<()> {
+    let mut encoder = Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+    encoder.set_frame_delay(delay, 1000)?;
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        writer.write_image_data(frame)?;
+    }
+
+    Ok(())
+}
+
 /// Save layer data as a greyscale PNG file.
 pub fn save_image_grey(path: impl AsRef<Path>, data: &[u8], width: u32, height: u32) -> Result<()> {
     let mut encoder = Encoder::new(File::create(path)?, width, height);
@@ -121,3 +177,149 @@ pub fn save_image_grey(path: impl AsRef<Path>, data: &[u8], width: u32, height:
 
     Ok(())
 }
+
+/// Save high precision layer data (in 0.0..=255.0 range) as a 16-bit greyscale PNG file.
+pub fn save_image_grey16(path: impl AsRef<Path>, data: &[f32], width: u32, height: u32) -> Result<()> {
+    let mut encoder = Encoder::new(File::create(path)?, width, height);
+    encoder.set_color(ColorType::Grayscale);
+    encoder.set_depth(BitDepth::Sixteen);
+    let mut writer = encoder.write_header()?;
+    let data: Vec<u8> = data.iter().flat_map(|x| to_u16(*x).to_be_bytes()).collect();
+    writer.write_image_data(&data)?;
+
+    Ok(())
+}
+
+/// Save high precision layer data (in 0.0..=255.0 range) as a raw 16-bit little endian file.
+pub fn save_raw16(path: impl AsRef<Path>, data: &[f32]) -> Result<()> {
+    let data: Vec<u8> = data.iter().flat_map(|x| to_u16(*x).to_le_bytes()).collect();
+    fs::write(path, data)?;
+
+    Ok(())
+}
+
+/// Scale a value from 0.0..=255.0 range to full 16-bit range.
+fn to_u16(value: f32) -> u16 {
+    (value * 257.0).round().clamp(0.0, u16::MAX as f32) as u16
+}
+
+/// Mapping of raster cells to geographic coordinates (degrees of longitude and latitude).
+pub struct GeoTransform {
+    /// Coordinates of the top-left (north-west) raster corner.
+    pub origin: [f64; 2],
+    /// Size of a single raster cell.
+    pub cell_size: [f64; 2],
+}
+
+impl GeoTransform {
+    /// Equirectangular projection of the whole world onto the raster.
+    pub fn equirectangular(width: u32, height: u32) -> Self {
+        Self {
+            origin: [-180.0, 90.0],
+            cell_size: [360.0 / width as f64, 180.0 / height as f64],
+        }
+    }
+}
+
+/// Save layer data as an ESRI ASCII grid file.
+pub fn save_ascii_grid(
+    path: impl AsRef<Path>,
+    data: &[f32],
+    width: u32,
+    height: u32,
+    transform: &GeoTransform,
+) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let [x, y] = transform.origin;
+    let [dx, dy] = transform.cell_size;
+    writeln!(writer, "ncols {}", width)?;
+    writeln!(writer, "nrows {}", height)?;
+    writeln!(writer, "xllcorner {}", x)?;
+    writeln!(writer, "yllcorner {}", y - dy * height as f64)?;
+    // Non-square cells are not in the original format, but are widely supported.
+    if dx == dy {
+        writeln!(writer, "cellsize {}", dx)?;
+    } else {
+        writeln!(writer, "dx {}", dx)?;
+        writeln!(writer, "dy {}", dy)?;
+    }
+    for row in data.chunks_exact(width as usize) {
+        let row: Vec<String> = row.iter().map(|x| x.to_string()).collect();
+        writeln!(writer, "{}", row.join(" "))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Save layer data as a single band, 32-bit float GeoTIFF file in WGS 84 coordinates.
+pub fn save_geotiff(
+    path: impl AsRef<Path>,
+    data: &[f32],
+    width: u32,
+    height: u32,
+    transform: &GeoTransform,
+) -> Result<()> {
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+    const DOUBLE: u16 = 12;
+    const HEADER_SIZE: u32 = 8;
+    let [x, y] = transform.origin;
+    let [dx, dy] = transform.cell_size;
+    let pixel_scale = [dx, dy, 0.0];
+    let tiepoint = [0.0, 0.0, 0.0, x, y, 0.0];
+    // GeoKey directory header, geographic model, pixel is area, WGS 84.
+    let geokeys: [u16; 16] = [1, 1, 0, 3, 1024, 0, 1, 2, 1025, 0, 1, 1, 2048, 0, 1, 4326];
+    // File layout: header, image strip, GeoTIFF tag arrays, image file directory.
+    let image_size = width * height * 4;
+    let scale_offset = HEADER_SIZE + image_size;
+    let tiepoint_offset = scale_offset + 8 * pixel_scale.len() as u32;
+    let geokeys_offset = tiepoint_offset + 8 * tiepoint.len() as u32;
+    let ifd_offset = geokeys_offset + 2 * geokeys.len() as u32;
+    // Tag, type, count, value or offset. Entries must be sorted by tag.
+    let entries: [(u16, u16, u32, u32); 14] = [
+        (256, LONG, 1, width),                                   // ImageWidth
+        (257, LONG, 1, height),                                  // ImageLength
+        (258, SHORT, 1, 32),                                     // BitsPerSample
+        (259, SHORT, 1, 1),                                      // Compression: none
+        (262, SHORT, 1, 1),                                      // PhotometricInterpretation: black is zero
+        (273, LONG, 1, HEADER_SIZE),                             // StripOffsets
+        (277, SHORT, 1, 1),                                      // SamplesPerPixel
+        (278, LONG, 1, height),                                  // RowsPerStrip
+        (279, LONG, 1, image_size),                              // StripByteCounts
+        (284, SHORT, 1, 1),                                      // PlanarConfiguration: chunky
+        (339, SHORT, 1, 3),                                      // SampleFormat: IEEE float
+        (33550, DOUBLE, pixel_scale.len() as u32, scale_offset), // ModelPixelScale
+        (33922, DOUBLE, tiepoint.len() as u32, tiepoint_offset), // ModelTiepoint
+        (34735, SHORT, geokeys.len() as u32, geokeys_offset),    // GeoKeyDirectory
+    ];
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(b"II")?;
+    writer.write_all(&42u16.to_le_bytes())?;
+    writer.write_all(&ifd_offset.to_le_bytes())?;
+    for value in data {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    for value in pixel_scale.iter().chain(tiepoint.iter()) {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    for value in geokeys {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    writer.write_all(&(entries.len() as u16).to_le_bytes())?;
+    for (tag, kind, count, value) in entries {
+        writer.write_all(&tag.to_le_bytes())?;
+        writer.write_all(&kind.to_le_bytes())?;
+        writer.write_all(&count.to_le_bytes())?;
+        // Short values are left-justified in the value field.
+        match (kind, count) {
+            (SHORT, 1) => writer.write_all(&[(value as u16).to_le_bytes(), [0, 0]].concat())?,
+            _ => writer.write_all(&value.to_le_bytes())?,
+        }
+    }
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.flush()?;
+
+    Ok(())
+}
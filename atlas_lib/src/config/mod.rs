@@ -1,4 +1,5 @@
 pub mod climate;
+pub mod custom;
 pub mod deposit;
 pub mod gen;
 mod io;
@@ -8,6 +9,7 @@
 use atlas_macro::UiEditableEnum;
 use bevy::prelude::*;
 use climate::{BiomeConfig, ClimatePreviewMode};
+use custom::CustomLayerInfo;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeSet, marker::PhantomData};
 
@@ -15,6 +17,20 @@
 
 pub const MAX_WORLD_SIZE: u32 = 10000;
 
+/// Offsets of 4 direct neighbours of a tile.
+pub const NEIGHBOURS_4: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+/// Offsets of all 8 neighbours of a tile.
+pub const NEIGHBOURS_8: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
 /// Plugin responsible for the generator/simulator configuration and I/O.
 #[derive(Default)]
 pub struct ConfigPlugin<C: AtlasConfig> {
@@ -29,9 +45,11 @@ fn build(&self, app: &mut App) {
 
 pub trait AtlasConfig: Resource + Default {
     fn get_world_size(&self) -> (u32, u32);
+    fn get_world_model(&self) -> WorldModel;
     fn get_preview_model(&self) -> WorldModel;
     fn get_climate_preview(&self) -> ClimatePreviewMode;
     fn get_biome(&self, i: u8) -> &BiomeConfig;
+    fn get_custom_layers(&self) -> Vec<CustomLayerInfo>;
 
     /// Convert a point from Bevy world space to map space.
     fn world_to_map(&self, point: (f32, f32)) -> Option<(u32, u32)> {
@@ -124,61 +142,50 @@ fn index_to_chunk(&self, index: u32, chunk_size: u32) -> u32 {
         (y / chunk_size) * width_in_chunks + x / chunk_size
     }
 
+    /// Get index of a tile offset from the specified tile index, respecting the world model.
+    fn get_offset_tile(&self, index: u32, offset: (i32, i32)) -> Option<u32> {
+        self.get_world_model()
+            .offset_tile(self.get_world_size(), index, offset)
+    }
+
+    /// Get distance (in tiles) between two tiles, respecting the world model.
+    fn get_tile_distance(&self, a: u32, b: u32) -> f32 {
+        self.get_world_model().tile_distance(self.get_world_size(), a, b)
+    }
+
     /// Get 4 border tiles for the specified tile index.
     fn get_border_tiles_4(&self, index: u32) -> BTreeSet<u32> {
-        let (width, height) = self.get_world_size();
-        let mut result = BTreeSet::default();
-        let modi = index % width;
-        let divi = index / width;
-        if modi != 0 {
-            result.insert(index - 1);
-        };
-        if modi != width - 1 {
-            result.insert(index + 1);
-        }
-        if divi != 0 {
-            result.insert(index - width);
-        }
-        if divi != height - 1 {
-            result.insert(index + width);
-        }
+        let mut result: BTreeSet<u32> = NEIGHBOURS_4
+            .iter()
+            .filter_map(|x| self.get_offset_tile(index, *x))
+            .collect();
+        result.remove(&index);
         result
     }
 
     /// Get 8 border tiles for the specified tile index.
     fn get_border_tiles_8(&self, index: u32) -> BTreeSet<u32> {
-        let (width, height) = self.get_world_size();
-        let mut result = BTreeSet::default();
-        let modi = index % width;
-        let divi = index / width;
-        let (left, right, top, bottom) = (modi != 0, modi != width - 1, divi != 0, divi != height - 1);
-        if left {
-            result.insert(index - 1);
-            if top {
-                result.insert(index - width - 1);
-            }
-            if bottom {
-                result.insert(index + width - 1);
-            }
-        };
-        if right {
-            result.insert(index + 1);
-            if top {
-                result.insert(index - width + 1);
-            }
-            if bottom {
-                result.insert(index + width + 1);
-            }
-        }
-        if top {
-            result.insert(index - width);
-        }
-        if bottom {
-            result.insert(index + width);
-        }
+        let mut result: BTreeSet<u32> = NEIGHBOURS_8
+            .iter()
+            .filter_map(|x| self.get_offset_tile(index, *x))
+            .collect();
+        result.remove(&index);
         result
     }
 
+    /// Get all map points equivalent to this one, shifted by world width if the world wraps around.
+    fn get_wrapped_points(&self, point: (i32, i32)) -> Vec<(i32, i32)> {
+        let (width, _) = self.get_world_size();
+        match self.get_world_model() {
+            WorldModel::Flat => vec![point],
+            WorldModel::Globe => vec![
+                point,
+                (point.0 - width as i32, point.1),
+                (point.0 + width as i32, point.1),
+            ],
+        }
+    }
+
     /// Get 8 border tiles for the specified tile index and that tile too.
     fn get_border_tiles_9(&self, index: u32) -> BTreeSet<u32> {
         let mut res = self.get_border_tiles_8(index);
@@ -199,3 +206,50 @@ pub enum WorldModel {
     Flat,
     Globe,
 }
+
+impl WorldModel {
+    /// Get index of a tile offset from the specified tile index.
+    /// Flat maps end at their edges. Globes wrap around east-west,
+    /// and rows at the poles connect to the opposite side of the pole.
+    pub fn offset_tile(&self, world_size: (u32, u32), index: u32, offset: (i32, i32)) -> Option<u32> {
+        let (width, height) = (world_size.0 as i32, world_size.1 as i32);
+        let (x, y) = (
+            (index % world_size.0) as i32 + offset.0,
+            (index / world_size.0) as i32 + offset.1,
+        );
+        let (x, y) = match self {
+            WorldModel::Flat => {
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    return None;
+                }
+                (x, y)
+            }
+            WorldModel::Globe => {
+                let (x, y) = if y < 0 {
+                    (x + width / 2, -y - 1)
+                } else if y >= height {
+                    (x + width / 2, 2 * height - y - 1)
+                } else {
+                    (x, y)
+                };
+                if y < 0 || y >= height {
+                    return None;
+                }
+                (x.rem_euclid(width), y)
+            }
+        };
+        Some((y * width + x) as u32)
+    }
+
+    /// Get distance (in tiles) between two tiles. Globes wrap around east-west.
+    pub fn tile_distance(&self, world_size: (u32, u32), a: u32, b: u32) -> f32 {
+        let width = world_size.0;
+        let dx = (a % width).abs_diff(b % width);
+        let dy = (a / width).abs_diff(b / width);
+        let dx = match self {
+            WorldModel::Flat => dx,
+            WorldModel::Globe => dx.min(width - dx),
+        };
+        ((dx * dx + dy * dy) as f32).sqrt()
+    }
+}
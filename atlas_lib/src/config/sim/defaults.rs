@@ -4,6 +4,7 @@ impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
             world_size: [360, 180],
+            world_model: Default::default(),
         }
     }
 }
@@ -11,15 +12,20 @@ fn default() -> Self {
 impl Default for ScenarioConfig {
     fn default() -> Self {
         Self {
+            seed: 0,
             num_starts: 10,
             start_pop: 1.0,
             random_point_algorithm: Default::default(),
+            start_weight_layer: Default::default(),
             policy_mean: 0.5,
             policy_deviation: 0.2,
             start_points: vec![],
             lock_positions: false,
             lock_colors: false,
             lock_policies: false,
+            timelapse_interval: 0,
+            timelapse_preview: true,
+            stats_interval: 12,
             starting_land_claim_points: 70.0,
         }
     }
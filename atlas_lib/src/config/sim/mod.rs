@@ -6,9 +6,10 @@
     bevy::prelude::*,
     bevy_egui,
     config::{
-        climate::BiomeConfig, climate::ClimateConfig, deposit::DepositsConfig, AtlasConfig,
-        ClimatePreviewMode, WorldModel,
+        climate::BiomeConfig, climate::ClimateConfig, custom::CustomLayerInfo, deposit::DepositsConfig,
+        AtlasConfig, ClimatePreviewMode, WorldModel,
     },
+    domain::map::MapDataLayer,
     serde_derive::{Deserialize, Serialize},
     ui::sidebar::*,
     ui::{sidebar::SidebarControl, UiEditableEnum},
@@ -25,6 +26,8 @@ pub struct AtlasSimConfig {
     pub rules: RulesConfig,
     pub deposits: DepositsConfig,
     pub climate: ClimateConfig,
+    #[serde(default)]
+    pub custom_layers: Vec<CustomLayerInfo>,
 }
 
 impl AtlasConfig for AtlasSimConfig {
@@ -32,6 +35,10 @@ fn get_world_size(&self) -> (u32, u32) {
         (self.general.world_size[0], self.general.world_size[1])
     }
 
+    fn get_world_model(&self) -> WorldModel {
+        self.general.world_model
+    }
+
     fn get_preview_model(&self) -> WorldModel {
         WorldModel::Flat
     }
@@ -49,17 +56,36 @@ fn get_biome(&self, i: u8) -> &BiomeConfig {
             &self.climate.biomes[i]
         }
     }
+
+    fn get_custom_layers(&self) -> Vec<CustomLayerInfo> {
+        self.custom_layers.clone()
+    }
+}
+
+impl AtlasSimConfig {
+    /// Find a user-defined custom layer by its name.
+    pub fn get_custom_layer(&self, name: &str) -> Option<MapDataLayer> {
+        let index = self.custom_layers.iter().position(|x| x.name == name)?;
+        Some(MapDataLayer::Custom(index as u8))
+    }
 }
 
 /// Config for general world settings and preview.
 #[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
 pub struct GeneralConfig {
     pub world_size: [u32; 2],
+    #[serde(default)]
+    pub world_model: WorldModel,
 }
 
 /// Initial scenario config.
 #[derive(Debug, Deserialize, Resource, Serialize, MakeUi)]
 pub struct ScenarioConfig {
+    #[name("Seed")]
+    #[control(SidebarSliderRandom)]
+    #[add(speed(100.0))]
+    #[serde(default)]
+    pub seed: u32,
     #[name("# of Starting Points")]
     #[control(SidebarSlider)]
     #[add(clamp_range(1..=10000))]
@@ -67,6 +93,10 @@ pub struct ScenarioConfig {
     #[name("Random Start Point Algorithm")]
     #[control(SidebarEnumDropdown)]
     pub random_point_algorithm: StartPointAlgorithm,
+    #[name("Start Point Weight Layer")]
+    #[control(SidebarTextbox)]
+    #[serde(default)]
+    pub start_weight_layer: String,
     #[name("Starting Land Claim Points")]
     #[control(SidebarSlider)]
     #[add(clamp_range(0.0..=1000000.0))]
@@ -92,11 +122,32 @@ pub struct ScenarioConfig {
     #[name("Lock All Policies")]
     #[control(SidebarCheckbox)]
     pub lock_policies: bool,
+    #[name("Timelapse Interval [years]")]
+    #[control(SidebarSlider)]
+    #[add(clamp_range(0..=1000))]
+    #[serde(default)]
+    pub timelapse_interval: u32,
+    #[name("Timelapse Over Preview")]
+    #[control(SidebarCheckbox)]
+    #[serde(default = "default_timelapse_preview")]
+    pub timelapse_preview: bool,
+    /// Statistics sampling interval, measured in simulated months (0 disables recording).
+    /// Edited in the Statistics panel.
+    #[serde(default = "default_stats_interval")]
+    pub stats_interval: u32,
     #[name("Starting Points")]
     #[control(SidebarStructList)]
     pub start_points: Vec<StartingPoint>,
 }
 
+fn default_stats_interval() -> u32 {
+    12
+}
+
+fn default_timelapse_preview() -> bool {
+    true
+}
+
 #[derive(Default, Debug, Deserialize, Resource, Serialize, MakeUi)]
 pub struct StartingPoint {
     #[serde(skip)]
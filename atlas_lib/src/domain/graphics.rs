@@ -28,6 +28,10 @@
 #[derive(Resource)]
 pub struct MapLogicData {
     layers: HashMap<MapDataLayer, Vec<u8>>,
+    /// High precision data (in 0.0..=255.0 range) that byte layers are derived from, if there is any.
+    precise: HashMap<MapDataLayer, Vec<f32>>,
+    /// Raw images loaded from files, used by "from image" influence shapes.
+    images: HashMap<MapDataLayer, Vec<u8>>,
     climatemap: Vec<u8>,
 }
 
@@ -35,6 +39,8 @@ impl Default for MapLogicData {
     fn default() -> Self {
         Self {
             layers: Default::default(),
+            precise: Default::default(),
+            images: Default::default(),
             climatemap: vec![0u8; CLIMATEMAP_SIZE * CLIMATEMAP_SIZE],
         }
     }
@@ -48,21 +54,39 @@ pub fn get_layer(&self, layer: MapDataLayer) -> &[u8] {
     }
 
     pub fn get_layer_mut(&mut self, layer: MapDataLayer) -> &mut [u8] {
+        self.precise.remove(&layer);
         self.layers
             .get_mut(&layer)
             .expect("MapLogicData should map all layers")
     }
 
     pub fn pop_layer(&mut self, layer: MapDataLayer) -> Vec<u8> {
+        self.precise.remove(&layer);
         self.layers
             .remove(&layer)
             .expect("MapLogicData should map all layers")
     }
 
     pub fn put_layer(&mut self, layer: MapDataLayer, data: Vec<u8>) {
+        self.precise.remove(&layer);
         self.layers.insert(layer, data);
     }
 
+    /// Get high precision layer data, or convert byte data if there is none.
+    pub fn get_layer_precise(&self, layer: MapDataLayer) -> Vec<f32> {
+        match self.precise.get(&layer) {
+            Some(data) => data.clone(),
+            None => self.get_layer(layer).iter().map(|x| *x as f32).collect(),
+        }
+    }
+
+    /// Set high precision layer data, and derive byte data from it.
+    pub fn put_layer_precise(&mut self, layer: MapDataLayer, data: Vec<f32>) {
+        let bytes = data.iter().map(|x| x.round().clamp(0.0, 255.0) as u8).collect();
+        self.layers.insert(layer, bytes);
+        self.precise.insert(layer, data);
+    }
+
     pub fn resize_all_layers(&mut self, size: usize) {
         for (layer, data) in self.layers.iter_mut() {
             let bpp = match layer {
@@ -72,6 +96,19 @@ pub fn resize_all_layers(&mut self, size: usize) {
             };
             data.resize(size * bpp, 0);
         }
+        // High precision data and loaded images are stale after resizing.
+        self.precise.retain(|_, data| data.len() == size);
+        self.images.retain(|_, data| data.len() == size);
+    }
+
+    /// Get the raw image loaded for a layer, if there is one.
+    pub fn get_image(&self, layer: MapDataLayer) -> Option<&[u8]> {
+        self.images.get(&layer).map(|x| x.as_slice())
+    }
+
+    /// Keep a copy of a raw image loaded for a layer.
+    pub fn put_image(&mut self, layer: MapDataLayer, data: Vec<u8>) {
+        self.images.insert(layer, data);
     }
 
     pub fn get_climatemap(&self) -> &[u8] {
@@ -188,6 +225,17 @@ pub fn data_to_view<C: AtlasConfig>(data_layers: &MapLogicData, layer: MapDataLa
         MapDataLayer::RealTopography => expand_monochrome(data),
         MapDataLayer::TopographyFilter => expand_monochrome(data),
         MapDataLayer::Climate => climate_to_view(data, config),
+        MapDataLayer::Rivers => expand_monochrome(data),
+        MapDataLayer::Custom(i) => custom_to_view(data, i, config),
+    }
+}
+
+/// Convert custom layer data to an RGBA image, using the layer's color ramp.
+/// Undeclared layer slots are shown in greyscale.
+fn custom_to_view<C: AtlasConfig>(data: &[u8], i: u8, config: &C) -> Vec<u8> {
+    match config.get_custom_layers().get(i as usize) {
+        Some(info) => data.iter().flat_map(|x| info.get_color(*x)).collect(),
+        None => expand_monochrome(data),
     }
 }
 
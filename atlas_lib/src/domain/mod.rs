@@ -16,6 +16,7 @@ pub enum MapDataLayer {
         Temperature,
         Precipitation,
         Climate,
+        Rivers,
         #[invisible]
         Deposits,
         #[invisible]
@@ -27,6 +28,9 @@ pub enum MapDataLayer {
         TopographyInfluence,
         TemperatureInfluence,
         PrecipitationInfluence,
+        // User-defined
+        #[invisible]
+        Custom(u8),
     }
 
     impl MapDataLayer {
@@ -38,6 +42,7 @@ pub fn get_influence_layer(&self) -> Option<Self> {
                 MapDataLayer::Temperature => Some(MapDataLayer::TemperatureInfluence),
                 MapDataLayer::Precipitation => Some(MapDataLayer::PrecipitationInfluence),
                 MapDataLayer::Climate => None,
+                MapDataLayer::Rivers => None,
                 MapDataLayer::Deposits => None,
                 MapDataLayer::RealTopography => None,
                 MapDataLayer::TopographyFilter => None,
@@ -45,18 +50,23 @@ pub fn get_influence_layer(&self) -> Option<Self> {
                 MapDataLayer::TopographyInfluence => Some(MapDataLayer::TopographyInfluence),
                 MapDataLayer::TemperatureInfluence => Some(MapDataLayer::TemperatureInfluence),
                 MapDataLayer::PrecipitationInfluence => Some(MapDataLayer::PrecipitationInfluence),
+                MapDataLayer::Custom(_) => None,
             }
         }
     }
 
-    /// Array of all [`MapDataLayer`] variants.
-    pub const MAP_DATA_LAYERS: [MapDataLayer; 12] = [
+    /// Max number of user-defined custom layers.
+    pub const MAX_CUSTOM_LAYERS: usize = 8;
+
+    /// Array of all [`MapDataLayer`] variants, including every custom layer slot.
+    pub const MAP_DATA_LAYERS: [MapDataLayer; 13 + MAX_CUSTOM_LAYERS] = [
         MapDataLayer::Preview,
         MapDataLayer::Continents,
         MapDataLayer::Topography,
         MapDataLayer::Temperature,
         MapDataLayer::Precipitation,
         MapDataLayer::Climate,
+        MapDataLayer::Rivers,
         MapDataLayer::RealTopography,
         MapDataLayer::TopographyFilter,
         // Influence
@@ -64,19 +74,46 @@ pub fn get_influence_layer(&self) -> Option<Self> {
         MapDataLayer::TopographyInfluence,
         MapDataLayer::TemperatureInfluence,
         MapDataLayer::PrecipitationInfluence,
+        // User-defined
+        MapDataLayer::Custom(0),
+        MapDataLayer::Custom(1),
+        MapDataLayer::Custom(2),
+        MapDataLayer::Custom(3),
+        MapDataLayer::Custom(4),
+        MapDataLayer::Custom(5),
+        MapDataLayer::Custom(6),
+        MapDataLayer::Custom(7),
     ];
 
     /// Array of all exportable [`MapDataLayer`]s.
-    pub const EXPORT_DATA_LAYERS: [(MapDataLayer, &str); 7] = [
+    pub const EXPORT_DATA_LAYERS: [(MapDataLayer, &str); 8] = [
         (MapDataLayer::Continents, "continents.png"),
         (MapDataLayer::Topography, "topography.png"),
         (MapDataLayer::RealTopography, "realtopography.png"),
         (MapDataLayer::Temperature, "temperature.png"),
         (MapDataLayer::Precipitation, "precipitation.png"),
         (MapDataLayer::Climate, "climate.png"),
+        (MapDataLayer::Rivers, "rivers.png"),
         (MapDataLayer::Preview, "preview.png"),
     ];
 
+    /// Array of all [`MapDataLayer`]s exported with high precision, as 16-bit PNG files.
+    pub const EXPORT_PRECISE_LAYERS: [(MapDataLayer, &str); 2] = [
+        (MapDataLayer::Topography, "topography16.png"),
+        (MapDataLayer::RealTopography, "realtopography16.png"),
+    ];
+
+    /// Name of the raw 16-bit little endian heightmap file.
+    pub const EXPORT_RAW_HEIGHTMAP: &str = "realtopography.r16";
+
+    /// Array of all numeric [`MapDataLayer`]s exported as GIS rasters (file names without extension).
+    pub const EXPORT_GIS_LAYERS: [(MapDataLayer, &str); 4] = [
+        (MapDataLayer::Topography, "topography"),
+        (MapDataLayer::RealTopography, "realtopography"),
+        (MapDataLayer::Temperature, "temperature"),
+        (MapDataLayer::Precipitation, "precipitation"),
+    ];
+
     /// A graphical overlay over the map, but not necessarily a full layer.
     #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Resource, UiEditableEnum)]
     pub enum MapDataOverlay {
@@ -91,4 +128,7 @@ pub enum MapDataOverlay {
     pub fn is_sea(value: u8) -> bool {
         value <= 127
     }
+
+    /// Value of a lake tile in the rivers layer. Other non-zero values are rivers, greater meaning greater discharge.
+    pub const RIVER_LAKE: u8 = 255;
 }
@@ -12,12 +12,14 @@
 pub use bevy_prng;
 pub use bevy_rand;
 pub use egui_file;
+pub use egui_plot;
 pub use png;
 pub use rand;
 pub use rand_distr;
 pub use rstar;
 pub use serde;
 pub use serde_derive;
+pub use serde_json;
 pub use serde_with;
 pub use thiserror;
 pub use toml;
@@ -0,0 +1,389 @@
+use std::path::{Path, PathBuf};
+
+use atlas_lib::{
+    bevy::{ecs::system::RunSystemOnce, prelude::*, utils::HashMap},
+    bevy_prng::WyRand,
+    bevy_rand::{plugin::EntropyPlugin, resource::GlobalEntropy},
+    config::{save_config, sim::AtlasSimConfig, AtlasConfig, Error},
+    domain::{
+        graphics::{color_to_u8, MapLogicData},
+        map::MAP_DATA_LAYERS,
+    },
+};
+use serde::Serialize;
+
+use crate::{
+    map::{
+        ensure_starting_points, import_world, prepare_combat_tables, randomize_starts, seed_simulation,
+        spawn_polities,
+    },
+    sim::{
+        chronicle::{export_chronicle_json, Chronicle, CHRONICLE_NAME},
+        polity::{Polity, LEN_POL, LEN_SCI, LEN_TRAD},
+        region::Region,
+        save::{load_world_state, save_world_state, SimHistory, SimState, STATE_NAME},
+        stats::{export_stats_csv, SimStats, STATS_NAME},
+        time_to_string,
+        timelapse::{export_timelapse, import_timelapse, Timelapse, TIMELAPSE_DIR},
+        SimControl, SimLogicPlugin, SimMapData,
+    },
+};
+
+/// Command line flag that enables the headless mode.
+pub const HEADLESS_FLAG: &str = "--headless";
+
+const USAGE: &str = "Usage: atlas_sim --headless <world directory> <number of months> <summary output path> \
+                     [save directory]";
+
+/// Run the simulation without a window for a number of months and write a summary.
+/// Returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let (world_path, months, output_path, save_path) = match args {
+        [world, months, output, save @ ..] if save.len() <= 1 => match months.parse::<u32>() {
+            Ok(months) => (
+                PathBuf::from(world),
+                months,
+                PathBuf::from(output),
+                save.first().map(PathBuf::from),
+            ),
+            Err(_) => {
+                eprintln!("{}", USAGE);
+                return 2;
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+    match simulate(&world_path, months, &output_path, save_path.as_deref()) {
+        Ok(()) => {
+            println!("Summary written to {}", output_path.display());
+            0
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            1
+        }
+    }
+}
+
+/// Helper function
+///
+/// Set up a windowless app with just the simulation logic.
+pub fn make_headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, EntropyPlugin::<WyRand>::default()))
+        .init_resource::<AtlasSimConfig>()
+        .init_resource::<MapLogicData>()
+        .add_plugins(SimLogicPlugin);
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Helper function
+///
+/// Import the world and place polities in a headless app.
+/// If the world directory contains a saved simulation state, it is restored instead.
+pub fn setup_headless_world(app: &mut App, world_path: &Path) -> Result<(), String> {
+    // Import the world.
+    let mut logics = MapLogicData::default();
+    for layer in MAP_DATA_LAYERS {
+        logics.put_layer(layer, vec![]);
+    }
+    let (mut config, _) = import_world(world_path, &mut logics).map_err(|x| x.to_string())?;
+    let (width, height) = config.get_world_size();
+    logics.resize_all_layers((width * height) as usize);
+    let mut extras = SimMapData::default();
+    extras.tile_region.resize((width * height) as usize, None);
+    extras.tile_polity.resize((width * height) as usize, None);
+    // Continue a saved simulation.
+    if world_path.join(STATE_NAME).exists() {
+        let mut state = Some(load_world_state(world_path).map_err(|x| x.to_string())?);
+        prepare_combat_tables(&mut config);
+        app.insert_resource(config)
+            .insert_resource(logics)
+            .insert_resource(extras);
+        let history = app.world.run_system_once(
+            move |config: Res<AtlasSimConfig>,
+                  mut sim: ResMut<SimControl>,
+                  mut extras: ResMut<SimMapData>,
+                  mut rng: ResMut<GlobalEntropy<WyRand>>,
+                  mut commands: Commands| {
+                let state = state.take().expect("Always Some");
+                state.restore(&config, &mut sim, &mut extras, &mut rng, &mut commands)
+            },
+        );
+        let (mut stats, mut chronicle, mut timelapse) = default();
+        history.restore(&mut stats, &mut chronicle, &mut timelapse);
+        import_timelapse(&mut timelapse, &world_path.join(TIMELAPSE_DIR)).map_err(|x| x.to_string())?;
+        app.insert_resource(stats)
+            .insert_resource(chronicle)
+            .insert_resource(timelapse);
+        return Ok(());
+    }
+    // Prepare start points.
+    ensure_starting_points(&mut config);
+    if !randomize_starts(&mut config, &mut logics) {
+        return Err("Failed to choose unique random locations for all points.".to_string());
+    }
+    app.insert_resource(config)
+        .insert_resource(logics)
+        .insert_resource(extras);
+    // Spawn polities.
+    app.world.run_system_once(
+        |mut config: ResMut<AtlasSimConfig>,
+         mut extras: ResMut<SimMapData>,
+         mut rng: ResMut<GlobalEntropy<WyRand>>,
+         mut commands: Commands| {
+            spawn_polities(&mut config, &mut extras, &mut commands);
+            seed_simulation(&config, &mut rng);
+        },
+    );
+    Ok(())
+}
+
+/// Helper function
+///
+/// Advance the simulation by one month, regardless of real time.
+pub fn step_headless(app: &mut App) {
+    let mut sim = app.world.resource_mut::<SimControl>();
+    sim.time += 1;
+    sim.tick = true;
+    app.world.run_schedule(FixedUpdate);
+}
+
+/// Helper function
+///
+/// Do the actual work of the headless mode.
+fn simulate(
+    world_path: &Path,
+    months: u32,
+    output_path: &Path,
+    save_path: Option<&Path>,
+) -> Result<(), String> {
+    let mut app = make_headless_app();
+    setup_headless_world(&mut app, world_path)?;
+    for _ in 0..months {
+        step_headless(&mut app);
+    }
+    if let Some(save_path) = save_path {
+        std::fs::create_dir_all(save_path).map_err(|x| x.to_string())?;
+        save_headless_world(&mut app.world, save_path).map_err(|x| x.to_string())?;
+    }
+    // Timelapse goes to the save directory if there is one, otherwise next to the summary.
+    let timelapse_path = save_path
+        .or_else(|| output_path.parent())
+        .unwrap_or(Path::new("."))
+        .join(TIMELAPSE_DIR);
+    export_timelapse(app.world.resource::<Timelapse>(), &timelapse_path).map_err(|x| x.to_string())?;
+    let summary = make_summary(&mut app.world);
+    save_config(&summary, output_path).map_err(|x| x.to_string())
+}
+
+/// Helper function
+///
+/// Save the simulation state, so that it can be continued later (headless or not),
+/// along with recorded statistics, the chronicle and the timelapse.
+fn save_headless_world(world: &mut World, save_path: &Path) -> Result<(), Error> {
+    export_stats_csv(world.resource::<SimStats>(), save_path.join(STATS_NAME))?;
+    export_chronicle_json(world.resource::<Chronicle>(), save_path.join(CHRONICLE_NAME))?;
+    let history = SimHistory::capture(
+        world.resource::<SimStats>(),
+        world.resource::<Chronicle>(),
+        world.resource::<Timelapse>(),
+    );
+    let mut polities = world.query::<(Entity, &Polity)>();
+    let mut regions = world.query::<(Entity, &Region)>();
+    let state = SimState::capture(
+        world.resource::<SimControl>(),
+        world.resource::<GlobalEntropy<WyRand>>(),
+        polities.iter(world),
+        regions.iter(world),
+        world.resource::<SimMapData>(),
+        history,
+    );
+    save_world_state(
+        save_path,
+        world.resource::<MapLogicData>(),
+        world.resource::<AtlasSimConfig>(),
+        &state,
+    )
+}
+
+/// Final simulation state summary.
+#[derive(Debug, PartialEq, Serialize)]
+struct SimSummary {
+    /// Final simulation date.
+    date: String,
+    /// Number of simulated months.
+    months: u32,
+    /// Number of conflicts ever started.
+    total_conflicts: u32,
+    /// Number of conflicts still in progress.
+    active_conflicts: u32,
+    /// All polities (including ones without land).
+    polities: Vec<PolitySummary>,
+}
+
+/// Final state of a single polity.
+#[derive(Debug, PartialEq, Serialize)]
+struct PolitySummary {
+    id: u32,
+    color: [u8; 3],
+    alive: bool,
+    regions: u32,
+    tiles: u32,
+    population: f32,
+    military: f32,
+    avg_stability: f32,
+    avg_health: f32,
+    tech: [f32; LEN_SCI],
+    traditions: [f32; LEN_TRAD],
+    policies: [f32; LEN_POL],
+    great_works: u32,
+    great_people: u32,
+    conflicts: u32,
+}
+
+/// Helper function
+///
+/// Gather summary data from the simulation world.
+fn make_summary(world: &mut World) -> SimSummary {
+    let sim = world.resource::<SimControl>();
+    let (date, months) = (time_to_string(sim.time), sim.time);
+    let extras = world.resource::<SimMapData>();
+    let (total_conflicts, active_conflicts) = (extras.conflict_counter, extras.conflicts.len() as u32);
+    let mut tiles = HashMap::<Entity, u32>::default();
+    for polity in extras.tile_polity.iter().flatten() {
+        *tiles.entry(*polity).or_default() += 1;
+    }
+    let mut polities: Vec<_> = world
+        .query::<(Entity, &Polity)>()
+        .iter(world)
+        .map(|(entity, polity)| PolitySummary {
+            id: entity.index(),
+            color: color_to_u8(&polity.color),
+            alive: !polity.regions.is_empty(),
+            regions: polity.regions.len() as u32,
+            tiles: tiles.get(&entity).copied().unwrap_or_default(),
+            population: polity.population,
+            military: polity.jobs.military,
+            avg_stability: polity.avg_stability,
+            avg_health: polity.avg_health,
+            tech: polity.tech.map(|x| x[0]),
+            traditions: polity.traditions.map(|x| x[0]),
+            policies: polity.policies,
+            great_works: polity.great_works.len() as u32,
+            great_people: polity.great_people.len() as u32,
+            conflicts: polity.conflicts.len() as u32,
+        })
+        .collect();
+    polities.sort_by_key(|x| x.id);
+    SimSummary {
+        date,
+        months,
+        total_conflicts,
+        active_conflicts,
+        polities,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atlas_lib::{
+        config::{deposit::DepositChunk, save_image, save_image_grey, sim::CONFIG_NAME},
+        domain::map::{MapDataLayer, EXPORT_DATA_LAYERS},
+    };
+
+    use super::*;
+
+    /// Write a small world with a single temperate island.
+    fn write_test_world(path: &Path) {
+        let (width, height) = (32, 16);
+        let mut config = AtlasSimConfig::default();
+        config.general.world_size = [width, height];
+        config.scenario.num_starts = 4;
+        config.scenario.seed = 7;
+        let island: Vec<bool> = (0..width * height)
+            .map(|i| (4..28).contains(&(i % width)) && (3..13).contains(&(i / width)))
+            .collect();
+        config.deposits.chunk_size = 8;
+        config.deposits.chunks = vec![DepositChunk::default(); 8];
+        for i in (0..width * height).filter(|x| island[*x as usize]) {
+            let chunk = config.index_to_chunk(i, 8) as usize;
+            config.deposits.chunks[chunk].tile_count += 1;
+        }
+        std::fs::create_dir_all(path).unwrap();
+        save_config(&config, path.join(CONFIG_NAME)).unwrap();
+        for (layer, name) in EXPORT_DATA_LAYERS {
+            let path = path.join(name);
+            let data: Vec<u8> = match layer {
+                MapDataLayer::Preview => {
+                    save_image(path, &vec![255; island.len() * 4], width, height).unwrap();
+                    continue;
+                }
+                MapDataLayer::Continents => island.iter().map(|x| if *x { 255 } else { 0 }).collect(),
+                MapDataLayer::Climate => island.iter().map(|x| if *x { 13 } else { 0 }).collect(),
+                MapDataLayer::Rivers => vec![0; island.len()],
+                _ => vec![128; island.len()],
+            };
+            save_image_grey(path, &data, width, height).unwrap();
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_summary() {
+        let path = std::env::temp_dir().join(format!("atlas_sim_headless_{}", std::process::id()));
+        write_test_world(&path);
+        let run = || {
+            let mut app = make_headless_app();
+            setup_headless_world(&mut app, &path).unwrap();
+            for _ in 0..36 {
+                step_headless(&mut app);
+            }
+            make_summary(&mut app.world)
+        };
+        let (first, second) = (run(), run());
+        std::fs::remove_dir_all(&path).unwrap();
+        assert_eq!(first.polities.len(), 4);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn loaded_save_continues_where_it_stopped() {
+        let path = std::env::temp_dir().join(format!("atlas_sim_headless_save_{}", std::process::id()));
+        let save_path = path.join("save");
+        write_test_world(&path);
+        std::fs::create_dir_all(&save_path).unwrap();
+        let run = |mut app: App, world_path: &Path, months: u32| {
+            setup_headless_world(&mut app, world_path).unwrap();
+            for _ in 0..months {
+                step_headless(&mut app);
+            }
+            app
+        };
+        let mut continuous = run(make_headless_app(), &path, 1800);
+        let mut saved = run(make_headless_app(), &path, 1200);
+        save_headless_world(&mut saved.world, &save_path).unwrap();
+        // Free some entities first, like the GUI does when replacing a world,
+        // so that the loaded entities reuse them in reverse order.
+        let mut app = make_headless_app();
+        let freed: Vec<_> = (0..64).map(|_| app.world.spawn_empty().id()).collect();
+        for entity in freed {
+            app.world.despawn(entity);
+        }
+        let mut loaded = run(app, &save_path, 600);
+        // Entity ids are not preserved by loading, only their order.
+        let summary = |app: &mut App| {
+            let mut summary = make_summary(&mut app.world);
+            summary.polities.iter_mut().for_each(|x| x.id = 0);
+            summary
+        };
+        let (first, second) = (summary(&mut continuous), summary(&mut loaded));
+        std::fs::remove_dir_all(&path).unwrap();
+        assert!(first.total_conflicts > 0);
+        assert_eq!(first, second);
+    }
+}
@@ -1,6 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+mod headless;
 mod map;
 mod sim;
 mod ui;
@@ -15,6 +16,11 @@
 
 /// Application entry point.
 fn main() {
+    // Run without a window if requested.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).is_some_and(|x| x == headless::HEADLESS_FLAG) {
+        std::process::exit(headless::run(&args[2..]));
+    }
     App::new()
         .add_plugins(
             DefaultPlugins
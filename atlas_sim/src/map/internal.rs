@@ -4,7 +4,7 @@
         utils::{hashbrown::HashSet, HashMap},
     },
     config::{
-        sim::{AtlasSimConfig, StartPointAlgorithm},
+        sim::{AtlasSimConfig, PolityConfig, StartPointAlgorithm, StartingPoint},
         AtlasConfig,
     },
     domain::{
@@ -61,6 +61,14 @@ pub fn calc_start_point_weights(
         }
         StartPointAlgorithm::Uniform => {}
     };
+    // Scale weights by a custom layer if requested.
+    if let Some(layer) = config.get_custom_layer(&config.scenario.start_weight_layer) {
+        let uniform = matches!(
+            config.scenario.random_point_algorithm,
+            StartPointAlgorithm::Uniform
+        );
+        custom_layer_weight(&mut weights, logics.get_layer(layer), uniform);
+    }
     // Prep one-tile-high strips for speedup.
     let strip_weights: Vec<u32> = match config.scenario.random_point_algorithm {
         StartPointAlgorithm::Uniform => weights
@@ -75,6 +83,30 @@ pub fn calc_start_point_weights(
     (weights, strip_weights)
 }
 
+/// Add or remove start points to match the requested number of starts, and keep them inside the map.
+pub fn ensure_starting_points(config: &mut AtlasSimConfig) {
+    let diff = config.scenario.num_starts as i32 - config.scenario.start_points.len() as i32;
+    if diff >= 0 {
+        for _ in 0..diff as usize {
+            config.scenario.start_points.push(StartingPoint {
+                polity: PolityConfig {
+                    population: config.scenario.start_pop,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        }
+    } else {
+        for _ in 0..(-diff as usize) {
+            config.scenario.start_points.pop();
+        }
+    }
+    for point in &mut config.scenario.start_points {
+        point.position[0] = point.position[0].clamp(0, config.general.world_size[0] - 1);
+        point.position[1] = point.position[1].clamp(0, config.general.world_size[1] - 1);
+    }
+}
+
 pub fn randomize_start_points(
     config: &mut AtlasSimConfig,
     rng: &mut impl Rng,
@@ -226,6 +258,15 @@ fn habitability_weight<'a>(
         .map(|(c, w)| (config.get_biome(*c).habitability * 1000.0) as u32 * w)
 }
 
+/// Scale weights by custom layer values, so that tiles with low values are less likely to be chosen.
+fn custom_layer_weight(weights: &mut [u32], layer: &[u8], uniform: bool) {
+    for (weight, value) in weights.iter_mut().zip(layer) {
+        let scaled = *weight as u64 * *value as u64;
+        // Uniform weights are only 0 or 1, don't lose precision.
+        *weight = if uniform { scaled } else { scaled.div_ceil(255) } as u32;
+    }
+}
+
 fn kernel_sum(input: &[u32], output: &mut [u32], width: usize, height: usize) {
     for x in 1..(width - 1) {
         for y in 1..(height - 1) {
@@ -247,3 +288,35 @@ fn kernel_sum(input: &[u32], output: &mut [u32], width: usize, height: usize) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use atlas_lib::config::custom::CustomLayerInfo;
+
+    use super::*;
+
+    #[test]
+    fn custom_layer_scales_start_weights() {
+        let mut config = AtlasSimConfig::default();
+        config.scenario.random_point_algorithm = StartPointAlgorithm::Uniform;
+        config.custom_layers = vec![CustomLayerInfo {
+            name: "Fertility".to_string(),
+            ..default()
+        }];
+        let mut logics = MapLogicData::default();
+        logics.put_layer(MapDataLayer::Continents, vec![0, 255, 255, 255]);
+        logics.put_layer(MapDataLayer::Custom(0), vec![255, 0, 128, 255]);
+        // No layer chosen.
+        let (weights, _) = calc_start_point_weights(&config, &mut logics, 2, 2);
+        assert_eq!(weights, [0, 1, 1, 1]);
+        // Unknown layer.
+        config.scenario.start_weight_layer = "Soil".to_string();
+        let (weights, _) = calc_start_point_weights(&config, &mut logics, 2, 2);
+        assert_eq!(weights, [0, 1, 1, 1]);
+        // Sea stays excluded, land is scaled by the layer.
+        config.scenario.start_weight_layer = "Fertility".to_string();
+        let (weights, strips) = calc_start_point_weights(&config, &mut logics, 2, 2);
+        assert_eq!(weights, [0, 0, 128, 255]);
+        assert_eq!(strips, [1, 383]);
+    }
+}
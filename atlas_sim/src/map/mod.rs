@@ -1,5 +1,7 @@
 mod internal;
 
+use std::path::Path;
+
 use atlas_lib::{
     base::{
         events::EventStruct,
@@ -10,14 +12,16 @@
     bevy_prng::WyRand,
     bevy_rand::resource::GlobalEntropy,
     config::{
+        custom::check_custom_layers,
         load_config, load_image, load_image_grey,
         sim::{AtlasSimConfig, CONFIG_NAME},
-        AtlasConfig,
+        AtlasConfig, Error,
     },
     domain::{
         graphics::{MapLogicData, WorldGlobeMesh, WorldMapMesh},
         map::{MapDataLayer, MapDataOverlay, EXPORT_DATA_LAYERS},
     },
+    rand::SeedableRng,
     weighted_rand::builder::{NewBuilder, WalkerTableBuilder},
 };
 use internal::randomize_point_policies;
@@ -27,14 +31,20 @@
         calc_start_point_weights, create_overlays, randomize_point_color, randomize_start_points,
     },
     sim::{
+        chronicle::{export_chronicle_json, Chronicle},
         polity::Polity,
         region::{spawn_region_with_city, Region},
+        save::{load_world_state, save_world_state, SimHistory, SimState},
+        stats::{export_stats_csv, SimStats},
+        timelapse::{export_timelapse, import_timelapse, Timelapse, TIMELAPSE_DIR},
         SimControl, SimMapData,
     },
     ui::{AtlasSimUi, MapOverlay},
 };
 
-pub use internal::get_random_policies;
+pub use internal::{ensure_starting_points, get_random_policies};
+
+const RANDOMIZE_ERROR: &str = "Failed to choose unique random locations for all points. Try again.";
 
 /// Plugin responsible for the world graphics and generation.
 pub struct MapPlugin;
@@ -44,6 +54,17 @@ fn build(&self, app: &mut App) {
         app.add_plugins(MapPluginBase::<AtlasSimConfig>::default())
             .add_systems(Update, update_event_import_start.run_if(check_event_import_start))
             .add_systems(Update, update_event_random_start.run_if(check_event_random_start))
+            .add_systems(Update, update_event_import_world.run_if(check_event_import_world))
+            .add_systems(Update, update_event_export_world.run_if(check_event_export_world))
+            .add_systems(Update, update_event_export_stats.run_if(check_event_export_stats))
+            .add_systems(
+                Update,
+                update_event_export_chronicle.run_if(check_event_export_chronicle),
+            )
+            .add_systems(
+                Update,
+                update_event_export_timelapse.run_if(check_event_export_timelapse),
+            )
             .add_systems(Update, update_event_overlay_changed)
             .add_systems(
                 Update,
@@ -59,6 +80,41 @@ pub fn check_event_import_start(events: Res<EventStruct>) -> bool {
     events.import_start_request.is_some()
 }
 
+/// Run condition
+///
+/// Check if "import world state" event needs handling.
+pub fn check_event_import_world(events: Res<EventStruct>) -> bool {
+    events.import_world_request.is_some()
+}
+
+/// Run condition
+///
+/// Check if "export world state" event needs handling.
+pub fn check_event_export_world(events: Res<EventStruct>) -> bool {
+    events.export_world_request.is_some()
+}
+
+/// Run condition
+///
+/// Check if "export statistics" event needs handling.
+pub fn check_event_export_stats(events: Res<EventStruct>) -> bool {
+    events.export_stats_request.is_some()
+}
+
+/// Run condition
+///
+/// Check if "export chronicle" event needs handling.
+pub fn check_event_export_chronicle(events: Res<EventStruct>) -> bool {
+    events.export_chronicle_request.is_some()
+}
+
+/// Run condition
+///
+/// Check if "export timelapse" event needs handling.
+pub fn check_event_export_timelapse(events: Res<EventStruct>) -> bool {
+    events.export_timelapse_request.is_some()
+}
+
 /// Run condition
 ///
 /// Check if "randomize start points" event needs handling.
@@ -87,40 +143,20 @@ pub fn update_event_import_start(
     commands: Commands,
 ) {
     let base_path = events.import_start_request.take().expect("Always Some");
-    // Import config.
-    let path = base_path.join(CONFIG_NAME);
-    match load_config(path) {
-        Ok(data) => {
+    let mut regen_layers = match import_world(&base_path, &mut logics) {
+        Ok((data, regen_layers)) => {
             *config = data;
             events.world_model_changed = Some(());
+            regen_layers
         }
         Err(error) => {
             events.error_window = Some(error.to_string());
             return;
         }
-    }
-    // Import all layers.
-    let (width, height) = (config.general.world_size[0], config.general.world_size[1]);
-    let mut regen_layers = vec![];
-    for (layer, name) in EXPORT_DATA_LAYERS {
-        let path = base_path.join(name);
-        let result = match layer {
-            MapDataLayer::Preview => load_image(path, width, height),
-            _ => load_image_grey(path, width, height),
-        };
-        match result {
-            Ok(data) => {
-                logics.put_layer(layer, data);
-                regen_layers.push(layer);
-            }
-            Err(error) => {
-                events.error_window = Some(error.to_string());
-                return;
-            }
-        };
-    }
+    };
     regen_layers.push(MapDataLayer::Preview);
     // Resize if needed.
+    let (width, height) = config.get_world_size();
     resize_helper(commands, config.as_ref(), map, globe, logics);
     extras.tile_region.resize((width * height) as usize, None);
     extras.tile_polity.resize((width * height) as usize, None);
@@ -131,35 +167,194 @@ pub fn update_event_import_start(
 
 /// Update system
 ///
-/// Randomize scenario start points.
-pub fn update_event_random_start(
+/// Import a saved simulation and continue it.
+pub fn update_event_import_world(
     mut events: ResMut<EventStruct>,
     mut logics: ResMut<MapLogicData>,
     mut config: ResMut<AtlasSimConfig>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    query: Query<(Entity, &MapOverlay), With<MapOverlay>>,
-    commands: Commands,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut extras: ResMut<SimMapData>,
+    mut sim: ResMut<SimControl>,
+    mut stats: ResMut<SimStats>,
+    mut chronicle: ResMut<Chronicle>,
+    mut timelapse: ResMut<Timelapse>,
+    mut ui_state: ResMut<AtlasSimUi>,
     mut ui_base: ResMut<UiStateBase>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    map: Query<(Entity, &mut Visibility, &mut Transform), With<WorldMapMesh>>,
+    globe: Query<(Entity, &mut Visibility), (With<WorldGlobeMesh>, Without<WorldMapMesh>)>,
+    mut commands: Commands,
 ) {
-    events.randomize_starts_request.take().expect("Always Some");
+    let base_path = events.import_world_request.take().expect("Always Some");
+    let state = match load_world_state(&base_path) {
+        Ok(state) => state,
+        Err(error) => {
+            events.error_window = Some(error.to_string());
+            return;
+        }
+    };
+    let mut regen_layers = match import_world(&base_path, &mut logics) {
+        Ok((data, regen_layers)) => {
+            *config = data;
+            events.world_model_changed = Some(());
+            regen_layers
+        }
+        Err(error) => {
+            events.error_window = Some(error.to_string());
+            return;
+        }
+    };
+    regen_layers.push(MapDataLayer::Preview);
+    // Respawn simulation entities.
+    prepare_combat_tables(&mut config);
+    let history = state.restore(&config, &mut sim, &mut extras, &mut rng, &mut commands);
+    history.restore(&mut stats, &mut chronicle, &mut timelapse);
+    ui_state.chronicle.clear();
+    if let Err(error) = import_timelapse(&mut timelapse, &base_path.join(TIMELAPSE_DIR)) {
+        events.error_window = Some(format!("Failed to load timelapse frames: {}", error));
+        *timelapse = default();
+    }
+    // Resize if needed.
+    resize_helper(commands, config.as_ref(), map, globe, logics);
+    // Refresh layers.
+    events.regen_layer_request = Some(regen_layers);
+    ui_state.world_loaded = true;
+    ui_state.enter_simulation_mode();
+    // Force hide start point overlay.
+    ui_base.overlays[0] = false;
+}
+
+/// Update system
+///
+/// Save the running simulation.
+pub fn update_event_export_world(
+    mut events: ResMut<EventStruct>,
+    logics: Res<MapLogicData>,
+    config: Res<AtlasSimConfig>,
+    extras: Res<SimMapData>,
+    sim: Res<SimControl>,
+    rng: Res<GlobalEntropy<WyRand>>,
+    history: (Res<SimStats>, Res<Chronicle>, Res<Timelapse>),
+    polities: Query<(Entity, &Polity)>,
+    regions: Query<(Entity, &Region)>,
+) {
+    let base_path = events.export_world_request.take().expect("Always Some");
+    let (stats, chronicle, timelapse) = history;
+    let history = SimHistory::capture(&stats, &chronicle, &timelapse);
+    let state = SimState::capture(&sim, &rng, polities.iter(), regions.iter(), &extras, history);
+    let result = save_world_state(&base_path, &logics, &config, &state)
+        .and_then(|_| export_timelapse(&timelapse, &base_path.join(TIMELAPSE_DIR)));
+    if let Err(error) = result {
+        events.error_window = Some(error.to_string());
+    }
+}
+
+/// Update system
+///
+/// Save recorded statistics to a CSV file.
+pub fn update_event_export_stats(mut events: ResMut<EventStruct>, stats: Res<SimStats>) {
+    let path = events.export_stats_request.take().expect("Always Some");
+    if let Err(error) = export_stats_csv(&stats, path) {
+        events.error_window = Some(error.to_string());
+    }
+}
+
+/// Update system
+///
+/// Save the chronicle to a JSON file.
+pub fn update_event_export_chronicle(mut events: ResMut<EventStruct>, chronicle: Res<Chronicle>) {
+    let path = events.export_chronicle_request.take().expect("Always Some");
+    if let Err(error) = export_chronicle_json(&chronicle, path) {
+        events.error_window = Some(error.to_string());
+    }
+}
+
+/// Update system
+///
+/// Save captured timelapse frames to a directory.
+pub fn update_event_export_timelapse(mut events: ResMut<EventStruct>, timelapse: Res<Timelapse>) {
+    let path = events.export_timelapse_request.take().expect("Always Some");
+    if let Err(error) = export_timelapse(&timelapse, &path) {
+        events.error_window = Some(error.to_string());
+    }
+}
+
+/// Helper function
+///
+/// Load the simulation config and all exported layers (including custom layers) from a directory.
+/// Returns the config and the list of loaded layers.
+pub fn import_world(
+    base_path: &Path,
+    logics: &mut MapLogicData,
+) -> Result<(AtlasSimConfig, Vec<MapDataLayer>), Error> {
+    // Import config.
+    let config: AtlasSimConfig = load_config(base_path.join(CONFIG_NAME))?;
+    check_custom_layers(&config.custom_layers)?;
+    // Import all layers.
+    let (width, height) = config.get_world_size();
+    let mut layers = vec![];
+    for (layer, name) in EXPORT_DATA_LAYERS {
+        let path = base_path.join(name);
+        let data = match layer {
+            MapDataLayer::Preview => load_image(path, width, height),
+            // Worlds exported before rivers were added have no rivers.
+            MapDataLayer::Rivers if !path.exists() => Ok(vec![0; (width * height) as usize]),
+            _ => load_image_grey(path, width, height),
+        }?;
+        logics.put_layer(layer, data);
+        layers.push(layer);
+    }
+    // Import custom layers.
+    for (i, info) in config.custom_layers.iter().enumerate() {
+        let layer = MapDataLayer::Custom(i as u8);
+        let data = load_image_grey(base_path.join(info.file_name()), width, height)?;
+        logics.put_layer(layer, data);
+        layers.push(layer);
+    }
+    Ok((config, layers))
+}
+
+/// Helper function
+///
+/// Randomize start point positions, colors and policies (unless locked), based on the scenario seed.
+/// Returns `false` if some points couldn't be placed.
+pub fn randomize_starts(config: &mut AtlasSimConfig, logics: &mut MapLogicData) -> bool {
+    let rng = &mut WyRand::seed_from_u64(config.scenario.seed as u64);
     let (width, height) = config.get_world_size();
     let (width, height) = (width as usize, height as usize);
     // Generate tile weights.
-    let (weights, strip_weights) = calc_start_point_weights(&config, &mut logics, width, height);
+    let (weights, strip_weights) = calc_start_point_weights(config, logics, width, height);
     // Randomize data.
-    if !config.scenario.lock_positions
-        && !randomize_start_points(&mut config, rng.as_mut(), &weights, &strip_weights, width)
-    {
-        events.error_window =
-            Some("Failed to choose unique random locations for all points. Try again.".to_string());
+    let mut success = true;
+    if !config.scenario.lock_positions {
+        success = randomize_start_points(config, rng, &weights, &strip_weights, width);
     }
     if !config.scenario.lock_colors {
-        randomize_point_color(&mut config, rng.as_mut());
+        randomize_point_color(config, rng);
     }
     if !config.scenario.lock_policies {
-        randomize_point_policies(&mut config, rng.as_mut());
+        randomize_point_policies(config, rng);
+    }
+    success
+}
+
+/// Update system
+///
+/// Randomize scenario start points.
+pub fn update_event_random_start(
+    mut events: ResMut<EventStruct>,
+    mut logics: ResMut<MapLogicData>,
+    mut config: ResMut<AtlasSimConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &MapOverlay), With<MapOverlay>>,
+    commands: Commands,
+    mut ui_base: ResMut<UiStateBase>,
+) {
+    events.randomize_starts_request.take().expect("Always Some");
+    // Advance the seed, so every click gives new points that can still be reproduced from the config.
+    config.scenario.seed = config.scenario.seed.wrapping_add(1);
+    if !randomize_starts(&mut config, &mut logics) {
+        events.error_window = Some(RANDOMIZE_ERROR.to_string());
     }
     // Recreate overlay markers.
     create_overlays(&config, commands, &mut meshes, &mut materials, query);
@@ -200,19 +395,40 @@ pub fn update_event_start_simulation(
     mut config: ResMut<AtlasSimConfig>,
     mut sim: ResMut<SimControl>,
     mut extras: ResMut<SimMapData>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut images: ResMut<Assets<Image>>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
     mut commands: Commands,
     mut ui_base: ResMut<UiStateBase>,
-    asset_server: Res<AssetServer>,
 ) {
     events.simulation_start_request.take();
     sim.paused = false;
+    spawn_polities(&mut config, &mut extras, &mut commands);
+    seed_simulation(&config, &mut rng);
+    // Force hide start point overlay.
+    ui_base.overlays[0] = false;
+}
+
+/// Helper function
+///
+/// Reset the simulation RNG to the scenario seed, so that the same scenario always gives the same history.
+pub fn seed_simulation(config: &AtlasSimConfig, rng: &mut GlobalEntropy<WyRand>) {
+    *rng = GlobalEntropy::<WyRand>::seed_from_u64(config.scenario.seed as u64);
+}
+
+/// Helper function
+///
+/// Prepare combat action tables from configured weights.
+pub fn prepare_combat_tables(config: &mut AtlasSimConfig) {
     config.rules.combat.action_table_attacker =
         WalkerTableBuilder::new(&config.rules.combat.action_weights_attacker).build();
     config.rules.combat.action_table_defender =
         WalkerTableBuilder::new(&config.rules.combat.action_weights_defender).build();
+}
+
+/// Helper function
+///
+/// Prepare combat tables and spawn a polity with a single region for every enabled start point.
+pub fn spawn_polities(config: &mut AtlasSimConfig, extras: &mut SimMapData, commands: &mut Commands) {
+    prepare_combat_tables(config);
     // Spawn polities.
     for start in &config.scenario.start_points {
         if start.disabled {
@@ -230,18 +446,8 @@ pub fn update_event_start_simulation(
         let mut region = Region::new(polity_entity, city_entity, i);
         region.population = start.polity.population;
         region.land_claim_fund = config.scenario.starting_land_claim_points;
-        region.claim_tile(region_entity, i, 2.0, 0.0, &mut extras, &config);
-        spawn_region_with_city(
-            region_entity,
-            city_entity,
-            region,
-            &mut commands,
-            &mut meshes,
-            &mut images,
-            &mut materials,
-            &asset_server,
-            &config,
-        );
+        region.claim_tile(region_entity, i, 2.0, 0.0, extras, config);
+        spawn_region_with_city(region_entity, city_entity, region, commands);
         // Prep polity.
         let mut polity = Polity {
             this: Some(polity_entity),
@@ -251,7 +457,7 @@ pub fn update_event_start_simulation(
                 start.polity.color[2],
             ),
             population: start.polity.population,
-            regions: [region_entity].into(),
+            regions: [region_entity].into_iter().collect(),
             policies: start.polity.policies,
             next_policy: start.polity.next_policy,
             ..Default::default()
@@ -262,8 +468,6 @@ pub fn update_event_start_simulation(
         extras.tile_region[i as usize] = Some(region_entity);
         extras.tile_polity[i as usize] = Some(polity_entity);
         extras.rtree.insert(p);
-        extras.add_city_borders(i, &config);
+        extras.add_city_borders(i, config);
     }
-    // Force hide start point overlay.
-    ui_base.overlays[0] = false;
 }
@@ -0,0 +1,216 @@
+use std::{fmt::Display, path::Path};
+
+use atlas_lib::{
+    bevy::{ecs as bevy_ecs, prelude::*},
+    config::{save_json, Error},
+    domain::graphics::color_to_u8,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::sim::{
+    polity::{Polity, TRAD_LABELS},
+    time_to_string,
+};
+
+/// Name of the chronicle file written next to a headless save.
+pub const CHRONICLE_NAME: &str = "chronicle.json";
+
+pub const LEN_CHR: usize = 7;
+/// War declared
+pub const CHR_WAR_DECLARED: usize = 0;
+/// War concluded
+pub const CHR_WAR_CONCLUDED: usize = 1;
+/// City founded
+pub const CHR_CITY_FOUNDED: usize = 2;
+/// Region conquered
+pub const CHR_REGION_CONQUERED: usize = 3;
+/// Great person born
+pub const CHR_GREAT_PERSON: usize = 4;
+/// Great work completed
+pub const CHR_GREAT_WORK: usize = 5;
+/// Polity destroyed
+pub const CHR_POLITY_DESTROYED: usize = 6;
+
+pub const CHR_LABELS: [&str; LEN_CHR] = [
+    "Wars Declared",
+    "Wars Concluded",
+    "Cities Founded",
+    "Regions Conquered",
+    "Great People",
+    "Great Works",
+    "Polities Destroyed",
+];
+
+/// Log of notable historical events, in chronological order.
+#[derive(Resource, Default)]
+pub struct Chronicle {
+    pub entries: Vec<ChronicleEntry>,
+}
+
+impl Chronicle {
+    /// Add a new entry at the end of the log.
+    pub fn record(&mut self, time: u32, position: Option<u32>, event: ChronicleEvent) {
+        self.entries.push(ChronicleEntry {
+            time,
+            position,
+            event,
+        });
+    }
+}
+
+/// A single notable historical event.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChronicleEntry {
+    /// Time of the event, measured in simulated months.
+    pub time: u32,
+    /// Tile index of the place of the event, if any.
+    pub position: Option<u32>,
+    /// Event type and entities involved.
+    pub event: ChronicleEvent,
+}
+
+/// A polity taking part in a historical event, as it was at the time of the event.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PolityTag {
+    /// Polity entity, used for filtering. May no longer exist.
+    pub entity: Entity,
+    /// Polity map color at the time of the event.
+    pub color: [u8; 3],
+}
+
+impl PolityTag {
+    pub fn new(entity: Entity, polity: &Polity) -> Self {
+        Self {
+            entity,
+            color: color_to_u8(&polity.color),
+        }
+    }
+}
+
+impl Display for PolityTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [r, g, b] = self.color;
+        write!(f, "Polity #{:02X}{:02X}{:02X}", r, g, b)
+    }
+}
+
+/// Type of a historical event and all polities involved.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ChronicleEvent {
+    WarDeclared {
+        conflict: u32,
+        attacker: PolityTag,
+        defender: PolityTag,
+    },
+    WarConcluded {
+        conflict: u32,
+        attacker: PolityTag,
+        defender: PolityTag,
+        outcome: WarOutcome,
+    },
+    CityFounded {
+        polity: PolityTag,
+    },
+    RegionConquered {
+        from: PolityTag,
+        to: PolityTag,
+    },
+    GreatPerson {
+        polity: PolityTag,
+        tradition: u8,
+    },
+    GreatWork {
+        polity: PolityTag,
+        tradition: u8,
+    },
+    PolityDestroyed {
+        polity: PolityTag,
+        by: PolityTag,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum WarOutcome {
+    AttackersWon,
+    DefendersWon,
+    Draw,
+}
+
+impl ChronicleEvent {
+    /// Get the event type index (one of `CHR_*`).
+    pub fn kind(&self) -> usize {
+        match self {
+            Self::WarDeclared { .. } => CHR_WAR_DECLARED,
+            Self::WarConcluded { .. } => CHR_WAR_CONCLUDED,
+            Self::CityFounded { .. } => CHR_CITY_FOUNDED,
+            Self::RegionConquered { .. } => CHR_REGION_CONQUERED,
+            Self::GreatPerson { .. } => CHR_GREAT_PERSON,
+            Self::GreatWork { .. } => CHR_GREAT_WORK,
+            Self::PolityDestroyed { .. } => CHR_POLITY_DESTROYED,
+        }
+    }
+
+    /// Check if a polity took part in the event.
+    pub fn involves(&self, entity: Entity) -> bool {
+        match self {
+            Self::WarDeclared {
+                attacker, defender, ..
+            }
+            | Self::WarConcluded {
+                attacker, defender, ..
+            } => attacker.entity == entity || defender.entity == entity,
+            Self::CityFounded { polity, .. }
+            | Self::GreatPerson { polity, .. }
+            | Self::GreatWork { polity, .. } => polity.entity == entity,
+            Self::RegionConquered { from, to } => from.entity == entity || to.entity == entity,
+            Self::PolityDestroyed { polity, by } => polity.entity == entity || by.entity == entity,
+        }
+    }
+}
+
+impl ChronicleEntry {
+    /// Get a human readable description of the event.
+    pub fn describe(&self) -> String {
+        let what = match &self.event {
+            ChronicleEvent::WarDeclared {
+                attacker, defender, ..
+            } => {
+                format!("{} declared war on {}", attacker, defender)
+            }
+            ChronicleEvent::WarConcluded {
+                attacker,
+                defender,
+                outcome,
+                ..
+            } => match outcome {
+                WarOutcome::AttackersWon => format!("{} won the war against {}", attacker, defender),
+                WarOutcome::DefendersWon => format!("{} repelled the attack of {}", defender, attacker),
+                WarOutcome::Draw => format!("War between {} and {} ended in a draw", attacker, defender),
+            },
+            ChronicleEvent::CityFounded { polity } => format!("{} founded a new city", polity),
+            ChronicleEvent::RegionConquered { from, to } => {
+                format!("{} conquered a region from {}", to, from)
+            }
+            ChronicleEvent::GreatPerson { polity, tradition } => format!(
+                "A great person of {} tradition was born in {}",
+                TRAD_LABELS[*tradition as usize], polity
+            ),
+            ChronicleEvent::GreatWork { polity, tradition } => format!(
+                "A great work of {} tradition was completed in {}",
+                TRAD_LABELS[*tradition as usize], polity
+            ),
+            ChronicleEvent::PolityDestroyed { polity, by } => {
+                format!("{} was destroyed by {}", polity, by)
+            }
+        };
+        format!("{} - {}", time_to_string(self.time), what)
+    }
+}
+
+/// Helper function
+///
+/// Write the whole chronicle to a JSON file.
+pub fn export_chronicle_json(chronicle: &Chronicle, path: impl AsRef<Path>) -> Result<(), Error> {
+    save_json(&chronicle.entries, path)
+}
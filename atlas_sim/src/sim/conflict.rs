@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
+
 use atlas_lib::{
-    bevy::{ecs as bevy_ecs, prelude::*, utils::hashbrown::HashMap},
+    bevy::{ecs as bevy_ecs, prelude::*},
     bevy_egui,
     config::{sim::AtlasSimConfig, AtlasConfig},
     rand::Rng,
@@ -7,8 +9,10 @@
     weighted_rand::table::WalkerTable,
     MakeUi, UiEditableEnum,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::sim::{
+    chronicle::{Chronicle, ChronicleEvent, PolityTag, WarOutcome},
     polity::{
         Polity, Tribute, POL_MILITARIST, SCI_MEDICINE, SCI_MILTECH, STR_FORTRESS, TRAD_DIPLOMATIC,
         TRAD_MILITANT,
@@ -18,15 +22,15 @@
     SimMapData,
 };
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Conflict {
     pub id: u32,
     pub start_date: u32,
     pub concluded: bool,
     pub primary_attacker: Entity,
     pub primary_defender: Entity,
-    pub attackers: HashMap<Entity, ConflictMember>,
-    pub defenders: HashMap<Entity, ConflictMember>,
+    pub attackers: BTreeMap<Entity, ConflictMember>,
+    pub defenders: BTreeMap<Entity, ConflictMember>,
     //pub marker: Entity, // TODO simple conflict markers?
 }
 
@@ -79,7 +83,7 @@ pub fn is_opposing(&self, us: &Entity, them: &Entity) -> bool {
     }
 }
 
-#[derive(Clone, MakeUi)]
+#[derive(Clone, MakeUi, Serialize, Deserialize)]
 pub struct ConflictMember {
     #[name("Polity Id")]
     #[control(SidebarEntityLink)]
@@ -130,7 +134,7 @@ impl ConflictMember {
     }
 }
 
-#[derive(Clone, UiEditableEnum)]
+#[derive(Clone, UiEditableEnum, Serialize, Deserialize)]
 pub enum CombatAction {
     /// Disengage and do nothing.
     Surrender,
@@ -170,6 +174,8 @@ pub fn update(
         regions: &mut Query<&mut Region>,
         rng: &mut impl Rng,
         extras: &mut SimMapData,
+        chronicle: &mut Chronicle,
+        time: u32,
     ) {
         // Choose an action if not capitulated.
         let defenders_lost = Self::choose_actions(
@@ -186,10 +192,19 @@ pub fn update(
         );
         // If one side is fully defeated, end the conflict.
         if attackers_lost && defenders_lost {
-            self.conclude_draw(polities, extras, config);
+            self.conclude_draw(polities, extras, config, chronicle, time);
             return;
         } else if attackers_lost || defenders_lost {
-            self.conclude_victory(defenders_lost, polities, regions, extras, config, rng);
+            self.conclude_victory(
+                defenders_lost,
+                polities,
+                regions,
+                extras,
+                config,
+                rng,
+                chronicle,
+                time,
+            );
             return;
         }
         // Apply defender action.
@@ -232,7 +247,7 @@ pub fn update(
     }
 
     fn choose_actions(
-        members: &mut HashMap<Entity, ConflictMember>,
+        members: &mut BTreeMap<Entity, ConflictMember>,
         attrition: f32,
         action_table: &WalkerTable,
         rng: &mut impl Rng,
@@ -272,7 +287,7 @@ fn handle_actions(
         config: &AtlasSimConfig,
         polities: &mut Query<&mut Polity>,
         rng: &mut impl Rng,
-        members: &mut HashMap<Entity, ConflictMember>,
+        members: &mut BTreeMap<Entity, ConflictMember>,
         id: u32,
     ) -> (f32, f32, f32, f32, f32, u32, f32) {
         let randomness = (-config.rules.combat.randomness)..=config.rules.combat.randomness;
@@ -330,7 +345,10 @@ fn conclude_draw(
         polities: &mut Query<&mut Polity>,
         extras: &mut SimMapData,
         config: &AtlasSimConfig,
+        chronicle: &mut Chronicle,
+        time: u32,
     ) {
+        self.record_conclusion(polities, config, chronicle, time, WarOutcome::Draw);
         for (atk_e, _) in self.attackers.iter_mut() {
             let mut atk_p = unsafe { polities.get_unchecked(*atk_e).unwrap() };
             for (def_e, _) in self.defenders.iter_mut() {
@@ -370,7 +388,15 @@ fn conclude_victory(
         extras: &mut SimMapData,
         config: &AtlasSimConfig,
         rng: &mut impl Rng,
+        chronicle: &mut Chronicle,
+        time: u32,
     ) {
+        let outcome = if attackers_won {
+            WarOutcome::AttackersWon
+        } else {
+            WarOutcome::DefendersWon
+        };
+        self.record_conclusion(polities, config, chronicle, time, outcome);
         let mut winner_contrib = 0.0;
         let (winners, losers) = if attackers_won {
             (&mut self.attackers, &mut self.defenders)
@@ -388,15 +414,15 @@ fn conclude_victory(
             member.contribution *= loser_p.get_tradition_multiplier(config, TRAD_DIPLOMATIC);
         }
         // Make region claims.
-        let mut region_claims_num = HashMap::<Entity, HashMap<Entity, u32>>::default(); // <Winner <Loser, count>>
-        let mut tribute_claims = HashMap::<Entity, Vec<Tribute>>::default(); // <Loser, [Tribute]>
-        let mut region_claims = HashMap::<Entity, (Entity, f32)>::default(); // <Region, (Winner, claim)>
+        let mut region_claims_num = BTreeMap::<Entity, BTreeMap<Entity, u32>>::default(); // <Winner <Loser, count>>
+        let mut tribute_claims = BTreeMap::<Entity, Vec<Tribute>>::default(); // <Loser, [Tribute]>
+        let mut region_claims = BTreeMap::<Entity, (Entity, f32)>::default(); // <Region, (Winner, claim)>
         for (winner_e, winner_m) in winners.iter_mut() {
             let winner_e = *winner_e;
             let contribution_ratio = (winner_m.contribution / winner_contrib).max(0.0);
             // NOTE: Unsafe is ok, the same polity cannot be a winner and loser in a conflict.
             let mut winner_p = unsafe { polities.get_unchecked(winner_e).unwrap() };
-            let mut claims = HashMap::<Entity, u32>::default();
+            let mut claims = BTreeMap::<Entity, u32>::default();
             for (loser_e, loser_m) in losers.iter_mut() {
                 let mut loser_p = unsafe { polities.get_unchecked(*loser_e).unwrap() };
                 let loser_diplomacy = loser_p.get_tradition_multiplier(config, TRAD_DIPLOMATIC);
@@ -467,7 +493,7 @@ fn conclude_victory(
             region_claims_num.insert(winner_e, claims);
         }
         // Resolve tribute claims.
-        for (loser_e, mut tributes) in tribute_claims.drain() {
+        for (loser_e, mut tributes) in tribute_claims {
             let mut loser_p = polities.get_mut(loser_e).unwrap();
             // Do not pay tribute to a winner that claimed our regions.
             tributes.retain(|tribute| {
@@ -480,20 +506,35 @@ fn conclude_victory(
             });
             // Make the winner a global tribute account.
             for tribute in tributes.iter() {
-                let _ = extras.tributes.try_insert(tribute.receiver, (0.0, 0.0));
+                extras.tributes.entry(tribute.receiver).or_insert((0.0, 0.0));
             }
             // Give the loser a list of tributes to pay.
             loser_p.tributes.push(tributes);
         }
         // Resolve region claims.
-        for (region_e, (winner_e, _)) in region_claims.drain() {
+        for (region_e, (winner_e, _)) in region_claims {
             let mut region = regions.get_mut(region_e).unwrap();
-            let mut loser_p = polities.get_mut(region.polity).unwrap();
+            let loser_e = region.polity;
+            let winner_tag = PolityTag::new(winner_e, polities.get(winner_e).unwrap());
+            let mut loser_p = polities.get_mut(loser_e).unwrap();
             let pos = config.index_to_map_i(region.city_position);
             region.need_visual_update = true;
             loser_p.population -= region.population;
             loser_p.regions.remove(&region_e);
             loser_p.rtree.remove(&pos);
+            let loser_tag = PolityTag::new(loser_e, &loser_p);
+            let event = ChronicleEvent::RegionConquered {
+                from: loser_tag,
+                to: winner_tag,
+            };
+            chronicle.record(time, Some(region.city_position), event);
+            if loser_p.regions.is_empty() {
+                let event = ChronicleEvent::PolityDestroyed {
+                    polity: loser_tag,
+                    by: winner_tag,
+                };
+                chronicle.record(time, Some(region.city_position), event);
+            }
             region.rebel_rate = (region.rebel_rate
                 + config.rules.combat.base_rebel_rate
                 + loser_p.policies[POL_MILITARIST] * loser_p.get_tradition_multiplier(config, TRAD_MILITANT))
@@ -526,6 +567,30 @@ fn conclude_victory(
         }
         self.concluded = true;
     }
+
+    fn record_conclusion(
+        &self,
+        polities: &Query<&mut Polity>,
+        config: &AtlasSimConfig,
+        chronicle: &mut Chronicle,
+        time: u32,
+        outcome: WarOutcome,
+    ) {
+        let (Ok(attacker), Ok(defender)) = (
+            polities.get(self.primary_attacker),
+            polities.get(self.primary_defender),
+        ) else {
+            return;
+        };
+        let position = defender.any_city_position(config);
+        let event = ChronicleEvent::WarConcluded {
+            conflict: self.id,
+            attacker: PolityTag::new(self.primary_attacker, attacker),
+            defender: PolityTag::new(self.primary_defender, defender),
+            outcome,
+        };
+        chronicle.record(time, position, event);
+    }
 }
 
 impl ConflictMember {
@@ -1,55 +1,76 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use atlas_lib::{
-    bevy::{ecs as bevy_ecs, prelude::*, utils::HashMap},
+    bevy::{ecs as bevy_ecs, prelude::*},
     config::{sim::AtlasSimConfig, AtlasConfig},
     rstar::RTree,
 };
+use chronicle::Chronicle;
 use conflict::Conflict;
-use polity::PolityPlugin;
+use polity::{PolityPlugin, PolityVisualsPlugin};
+use serde::{Deserialize, Serialize};
+use stats::SimStats;
+use timelapse::Timelapse;
 
+pub mod chronicle;
 pub mod conflict;
 pub mod polity;
 pub mod region;
+pub mod save;
+pub mod stats;
+pub mod timelapse;
 pub mod ui;
 
 /// Plugin responsible for the actual simulation.
 pub struct SimPlugin;
 
 impl Plugin for SimPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((SimLogicPlugin, PolityVisualsPlugin))
+            .add_systems(FixedUpdate, tick);
+    }
+}
+
+/// Plugin with simulation logic only, without visuals and real time control.
+pub struct SimLogicPlugin;
+
+impl Plugin for SimLogicPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SimControl>()
             .init_resource::<SimMapData>()
-            .add_systems(FixedUpdate, tick)
+            .init_resource::<SimStats>()
+            .init_resource::<Chronicle>()
+            .init_resource::<Timelapse>()
             .add_plugins(PolityPlugin);
     }
 }
 
 /// Extra map data just for the simulation.
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct SimMapData {
     /// Owner regions of specific map tiles.
     pub tile_region: Vec<Option<Entity>>,
     /// Owner polities of specific map tiles.
     pub tile_polity: Vec<Option<Entity>>,
     /// Region city rtree.
+    #[serde(skip)]
     pub rtree: RTree<(i32, i32)>,
     /// Deferred region spawn data.
-    pub deferred_regions: HashMap<Entity, Vec<(u32, Entity, Entity)>>,
+    pub deferred_regions: BTreeMap<Entity, Vec<(u32, Entity, Entity)>>,
     /// Tiles occupied by cities and surroundings.
     pub city_borders: BTreeSet<u32>,
     /// Active conflicts.
-    pub conflicts: HashMap<u32, Conflict>,
+    pub conflicts: BTreeMap<u32, Conflict>,
     /// Counter for unique conflict ids.
     pub conflict_counter: u32,
     /// Polity pair map of current wars and truce time.
     pub war_map: WarMap,
     /// Pending tributes to polities (industry, wealth).
-    pub tributes: HashMap<Entity, (f32, f32)>,
+    pub tributes: BTreeMap<Entity, (f32, f32)>,
 }
 
-#[derive(Resource, Default)]
-pub struct WarMap(HashMap<EntityPair, (u32, u32)>);
+#[derive(Resource, Default, Clone)]
+pub struct WarMap(BTreeMap<EntityPair, (u32, u32)>);
 
 impl WarMap {
     pub fn get_war_map_num(&mut self, us: Entity, them: Entity) -> (u32, u32) {
@@ -68,7 +89,7 @@ pub fn inc_war_map_num(&mut self, us: Entity, them: Entity) -> u32 {
             *truce = 0;
             *num
         } else {
-            self.0.insert_unique_unchecked(pair, (1, 0));
+            self.0.insert(pair, (1, 0));
             1
         }
     }
@@ -82,13 +103,13 @@ pub fn dec_war_map_num(&mut self, us: Entity, them: Entity, truce: u32) -> u32 {
             }
             *num
         } else {
-            self.0.insert_unique_unchecked(pair, (0, truce));
+            self.0.insert(pair, (0, truce));
             0
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct EntityPair(Entity, Entity);
 
 impl EntityPair {
@@ -122,7 +143,14 @@ pub fn create_conflict(&mut self, start_date: u32, attacker: Entity, defender: E
         id
     }
 
-    pub fn add_conflict_member(&mut self, us_e: Entity, color: [u8; 3], id: u32, is_attacker: bool, build_up: u32) {
+    pub fn add_conflict_member(
+        &mut self,
+        us_e: Entity,
+        color: [u8; 3],
+        id: u32,
+        is_attacker: bool,
+        build_up: u32,
+    ) {
         let conflict = self.conflicts.get_mut(&id).unwrap();
         conflict.add_member(us_e, color, build_up, is_attacker);
         let enemies = if is_attacker {
@@ -1,9 +1,7 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use atlas_lib::{
-    bevy::{
-        ecs as bevy_ecs,
-        prelude::*,
-        utils::{hashbrown::HashMap, petgraph::matrix_graph::Zero, HashSet},
-    },
+    bevy::{ecs as bevy_ecs, prelude::*, utils::petgraph::matrix_graph::Zero},
     bevy_egui::{self},
     bevy_prng::WyRand,
     bevy_rand::resource::GlobalEntropy,
@@ -20,11 +18,16 @@
     MakeUi,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     map::get_random_policies,
     sim::{
         check_tick,
-        region::{spawn_region_with_city, City, Region},
+        chronicle::{Chronicle, ChronicleEvent, PolityTag},
+        region::{spawn_region_visuals, spawn_region_with_city, City, Region},
+        stats::update_stats,
+        timelapse::update_timelapse,
         ui::PolityUi,
         SimControl, SimMapData,
     },
@@ -35,7 +38,7 @@
 
 impl Plugin for PolityPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, update_visuals).add_systems(
+        app.add_systems(
             FixedUpdate,
             (
                 update_territory,
@@ -47,6 +50,8 @@ fn build(&self, app: &mut App) {
                 update_diplomacy,
                 update_war,
                 update_splits,
+                update_stats,
+                update_timelapse,
             )
                 .chain()
                 .run_if(check_tick),
@@ -54,8 +59,17 @@ fn build(&self, app: &mut App) {
     }
 }
 
+/// Polity and region visuals. Not needed when running headless.
+pub struct PolityVisualsPlugin;
+
+impl Plugin for PolityVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (update_new_visuals, update_visuals).chain());
+    }
+}
+
 /// A political entity that owns land and population.
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Polity {
     /// This entity.
     pub this: Option<Entity>,
@@ -78,8 +92,9 @@ pub struct Polity {
     /// Number of essential jobs (covering basic resource consumption).
     pub essential_jobs: f32,
     /// Owned regions.
-    pub regions: HashSet<Entity>,
+    pub regions: BTreeSet<Entity>,
     /// Region rtree.
+    #[serde(skip)]
     pub rtree: RTree<(i32, i32)>,
     /// Accumulated heritage.
     pub heritage: [f32; LEN_TRAD],
@@ -94,9 +109,9 @@ pub struct Polity {
     /// Average health of all regions/pops.
     pub avg_health: f32,
     /// Neighbouring polities and: their border regions, relations, and flip-flag.
-    pub neighbours: HashMap<Entity, (HashSet<Entity>, f32, bool)>,
+    pub neighbours: BTreeMap<Entity, (BTreeSet<Entity>, f32, bool)>,
     /// Active conflicts.
-    pub conflicts: HashSet<u32>,
+    pub conflicts: BTreeSet<u32>,
     /// Available reinforcements per conflict (this month).
     pub reinforcements: Option<(f32, f32)>,
     /// Damage dealt to forts (this month).
@@ -195,7 +210,7 @@ pub fn as_ui(&self, config: &AtlasSimConfig, extras: &SimMapData) -> PolityUi {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Tribute {
     pub receiver: Entity,
     pub fraction: f32,
@@ -212,7 +227,7 @@ pub fn new(receiver: Entity, fraction: f32, time_left: u32) -> Self {
     }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct GreatWork {
     /// Tradition associated with this great work.
     pub tradition: u8,
@@ -220,7 +235,7 @@ pub struct GreatWork {
     pub time: u32,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct GreatPerson {
     /// Tradition associated with this great person.
     pub tradition: u8,
@@ -230,7 +245,7 @@ pub struct GreatPerson {
     pub active: bool,
 }
 
-#[derive(Clone, Default, MakeUi)]
+#[derive(Clone, Default, MakeUi, Serialize, Deserialize)]
 pub struct JobStruct {
     #[name("Military")]
     #[control(SidebarSlider)]
@@ -463,14 +478,11 @@ fn update_construction(
     mut polities: Query<(Entity, &mut Polity)>,
     mut regions: Query<&mut Region>,
     sim: Res<SimControl>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut images: ResMut<Assets<Image>>,
     mut commands: Commands,
     mut rng: ResMut<GlobalEntropy<WyRand>>,
     mut extras: ResMut<SimMapData>,
     logics: Res<MapLogicData>,
-    asset_server: Res<AssetServer>,
+    mut chronicle: ResMut<Chronicle>,
 ) {
     if !extras.deferred_regions.is_empty() {
         // Do logic on newly spawned regions.
@@ -491,17 +503,11 @@ fn update_construction(
                     &mut extras,
                     &logics,
                 );
-                spawn_region_with_city(
-                    region_entity,
-                    city_entity,
-                    region,
-                    &mut commands,
-                    &mut meshes,
-                    &mut images,
-                    &mut materials,
-                    &asset_server,
-                    &config,
-                );
+                let event = ChronicleEvent::CityFounded {
+                    polity: PolityTag::new(polity_entity, &polity),
+                };
+                spawn_region_with_city(region_entity, city_entity, region, &mut commands);
+                chronicle.record(sim.time, Some(position), event);
             }
         }
         extras.deferred_regions = deferred_regions;
@@ -537,13 +543,31 @@ fn update_construction(
 /// Update culture.
 fn update_culture(
     config: Res<AtlasSimConfig>,
-    mut polities: Query<&mut Polity>,
+    mut polities: Query<(Entity, &mut Polity)>,
     sim: Res<SimControl>,
     mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut chronicle: ResMut<Chronicle>,
 ) {
     if sim.is_new_year() {
-        for mut polity in polities.iter_mut() {
-            polity.update_culture(&config, &sim, &mut rng)
+        for (entity, mut polity) in polities.iter_mut() {
+            let (people, works) = (polity.great_people.len(), polity.great_works.len());
+            polity.update_culture(&config, &sim, &mut rng);
+            let position = polity.any_city_position(&config);
+            let tag = PolityTag::new(entity, &polity);
+            for x in polity.great_people.iter().skip(people) {
+                let event = ChronicleEvent::GreatPerson {
+                    polity: tag,
+                    tradition: x.tradition,
+                };
+                chronicle.record(x.time, position, event);
+            }
+            for x in polity.great_works.iter().skip(works) {
+                let event = ChronicleEvent::GreatWork {
+                    polity: tag,
+                    tradition: x.tradition,
+                };
+                chronicle.record(x.time, position, event);
+            }
         }
     }
 }
@@ -579,7 +603,9 @@ fn update_diplomacy(
     regions: Query<&Region>,
     sim: Res<SimControl>,
     mut extras: ResMut<SimMapData>,
+    mut chronicle: ResMut<Chronicle>,
 ) {
+    let first_new_conflict = extras.conflict_counter;
     let vec: Vec<_> = polities
         .iter_mut()
         .map(|(e, mut x)| {
@@ -596,6 +622,23 @@ fn update_diplomacy(
             polity.update_diplomacy(&config, &polities, polity_e, &mut extras, sim.time);
         }
     }
+    // Record new wars.
+    for id in first_new_conflict..extras.conflict_counter {
+        let conflict = &extras.conflicts[&id];
+        let (Ok((_, attacker)), Ok((_, defender))) = (
+            polities.get(conflict.primary_attacker),
+            polities.get(conflict.primary_defender),
+        ) else {
+            continue;
+        };
+        let position = defender.any_city_position(&config);
+        let event = ChronicleEvent::WarDeclared {
+            conflict: id,
+            attacker: PolityTag::new(conflict.primary_attacker, attacker),
+            defender: PolityTag::new(conflict.primary_defender, defender),
+        };
+        chronicle.record(conflict.start_date, position, event);
+    }
 }
 
 /// Update system
@@ -607,10 +650,20 @@ fn update_war(
     mut regions: Query<&mut Region>,
     mut extras: ResMut<SimMapData>,
     mut rng: ResMut<GlobalEntropy<WyRand>>,
+    sim: Res<SimControl>,
+    mut chronicle: ResMut<Chronicle>,
 ) {
     let mut conflicts = std::mem::take(&mut extras.conflicts);
     conflicts.retain(|_, conflict| {
-        conflict.update(&config, &mut polities, &mut regions, rng.as_mut(), &mut extras);
+        conflict.update(
+            &config,
+            &mut polities,
+            &mut regions,
+            rng.as_mut(),
+            &mut extras,
+            &mut chronicle,
+            sim.time,
+        );
         if conflict.concluded {
             // TODO cleanup
             false
@@ -640,6 +693,32 @@ fn update_splits(
     }
 }
 
+/// Update system
+///
+/// Create meshes and materials for newly spawned regions and cities.
+fn update_new_visuals(
+    config: Res<AtlasSimConfig>,
+    query: Query<(Entity, &Region), Added<Region>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, region) in query.iter() {
+        spawn_region_visuals(
+            entity,
+            region,
+            &mut commands,
+            &mut meshes,
+            &mut images,
+            &mut materials,
+            &asset_server,
+            &config,
+        );
+    }
+}
+
 /// Update system
 ///
 /// Update polity & city visuals.
@@ -685,8 +764,8 @@ pub fn add_new_region(
         let conts = logics.get_layer(MapDataLayer::Continents);
         let mut all_tiles = vec![];
         let mut new_tiles = vec![];
-        let mut lookup: HashMap<u32, usize> = Default::default();
-        let mut region_vec: Vec<_> = self.regions.drain().collect();
+        let mut lookup: BTreeMap<u32, usize> = Default::default();
+        let mut region_vec: Vec<_> = std::mem::take(&mut self.regions).into_iter().collect();
         // Gather all polity tiles.
         for (i, region) in region_vec.iter().enumerate() {
             let mut region = regions.get_mut(*region).unwrap();
@@ -710,8 +789,18 @@ pub fn add_new_region(
                 continue;
             }
             let pos = config.index_to_map_i(tile);
-            let city_pos = self.rtree.nearest_neighbor(&pos).unwrap();
-            let city_tile = config.map_i_to_index(*city_pos);
+            // Check across the world edge too, if it wraps around.
+            let city_tile = config
+                .get_wrapped_points(pos)
+                .iter()
+                .filter_map(|x| self.rtree.nearest_neighbor(x))
+                .map(|x| config.map_i_to_index(*x))
+                .min_by(|a, b| {
+                    let a = config.get_tile_distance(tile, *a);
+                    let b = config.get_tile_distance(tile, *b);
+                    a.total_cmp(&b)
+                })
+                .unwrap();
             let i = lookup[&city_tile];
             new_tiles[i].push(tile);
         }
@@ -1708,6 +1797,11 @@ fn get_resource_yield(
         out
     }
 
+    /// Get the tile index of any city of this polity, to point at it on the map.
+    pub fn any_city_position(&self, config: &AtlasSimConfig) -> Option<u32> {
+        self.rtree.iter().next().map(|x| config.map_i_to_index(*x))
+    }
+
     #[inline(always)]
     fn get_city_cost_multiplier(&self, config: &AtlasSimConfig, city: f32) -> f32 {
         1.0 + config.rules.region.dev_level_cost * city.floor()
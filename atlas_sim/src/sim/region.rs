@@ -1,15 +1,18 @@
-use std::{collections::BTreeSet, f32::consts::FRAC_PI_2};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    f32::consts::FRAC_PI_2,
+};
 
 use atlas_lib::{
     bevy::{
         ecs as bevy_ecs,
         prelude::*,
         render::{mesh::PlaneMeshBuilder, render_resource::Extent3d},
-        utils::{HashMap, HashSet},
     },
     config::{sim::AtlasSimConfig, AtlasConfig},
     domain::map::{is_sea, MapDataOverlay},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::ui::MapOverlay;
 
@@ -22,7 +25,7 @@
 #[derive(Component)]
 pub struct City;
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Region {
     /// Centroid of owned land, in map coords.
     pub centroid: Vec2,
@@ -41,9 +44,9 @@ pub struct Region {
     /// Is the region big enough to split?
     pub can_split_size: bool,
     /// Map of # of tiles owned in resource chunks.
-    pub resource_chunks: HashMap<u32, u16>,
+    pub resource_chunks: BTreeMap<u32, u16>,
     /// Map of available deposits.
-    pub deposits: HashMap<u32, f32>,
+    pub deposits: BTreeMap<u32, f32>,
     /// Region population.
     pub population: f32,
     /// Owner polity.
@@ -289,8 +292,8 @@ pub fn update_expansion(
         weights
     }
 
-    pub fn get_border_regions(&self, extras: &SimMapData) -> HashMap<Entity, HashSet<Entity>> {
-        let mut map: HashMap<Entity, HashSet<Entity>> = Default::default();
+    pub fn get_border_regions(&self, extras: &SimMapData) -> BTreeMap<Entity, BTreeSet<Entity>> {
+        let mut map: BTreeMap<Entity, BTreeSet<Entity>> = Default::default();
         for tile in &self.border_tiles {
             let tile = *tile as usize;
             let polity = match &extras.tile_polity[tile] {
@@ -304,7 +307,7 @@ pub fn get_border_regions(&self, extras: &SimMapData) -> HashMap<Entity, HashSet
             if let Some(set) = map.get_mut(&polity) {
                 set.insert(region);
             } else {
-                map.insert(polity, [region].into());
+                map.insert(polity, [region].into_iter().collect());
             }
         }
         map
@@ -357,11 +360,22 @@ fn update_deposits_from_chunk(&mut self, config: &AtlasSimConfig, chunk: u32, co
     }
 }
 
+/// Spawn region and city logic components into pre-allocated entities.
 pub fn spawn_region_with_city(
     region_entity: Entity,
     city_entity: Entity,
     region: Region,
     commands: &mut Commands,
+) {
+    commands.get_entity(city_entity).unwrap().insert(City);
+    commands.get_entity(region_entity).unwrap().insert(region);
+}
+
+/// Add meshes, materials and map overlays to a newly spawned region and its city.
+pub fn spawn_region_visuals(
+    region_entity: Entity,
+    region: &Region,
+    commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     images: &mut Assets<Image>,
     materials: &mut Assets<StandardMaterial>,
@@ -369,8 +383,7 @@ pub fn spawn_region_with_city(
     config: &AtlasSimConfig,
 ) {
     let city_pos = config.index_to_world_centered(region.city_position);
-    commands.get_entity(city_entity).unwrap().insert((
-        City,
+    commands.get_entity(region.city).unwrap().insert((
         PbrBundle {
             mesh: meshes.add(PlaneMeshBuilder::new(Direction3d::Y, Vec2::ONE / 50.0)),
             material: materials.add(StandardMaterial {
@@ -389,7 +402,6 @@ pub fn spawn_region_with_city(
     ));
     let region_pos = config.centroid_to_world_centered(region.centroid.into());
     commands.get_entity(region_entity).unwrap().insert((
-        region,
         PbrBundle {
             mesh: meshes.add(PlaneMeshBuilder::new(Direction3d::Y, Vec2::ONE).build()),
             material: materials.add(StandardMaterial {
@@ -0,0 +1,361 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use atlas_lib::{
+    bevy::prelude::*,
+    bevy_prng::WyRand,
+    bevy_rand::resource::GlobalEntropy,
+    config::{
+        custom::check_custom_layers,
+        load_json, save_config, save_image, save_image_grey, save_json,
+        sim::{AtlasSimConfig, CONFIG_NAME},
+        AtlasConfig, Error,
+    },
+    domain::{
+        graphics::MapLogicData,
+        map::{MapDataLayer, EXPORT_DATA_LAYERS},
+    },
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::sim::{
+    chronicle::{Chronicle, ChronicleEntry, ChronicleEvent, PolityTag},
+    conflict::Conflict,
+    polity::Polity,
+    region::{spawn_region_with_city, Region},
+    stats::{PolityHistory, SimStats},
+    timelapse::Timelapse,
+    EntityPair, SimControl, SimMapData, WarMap,
+};
+
+/// Name of the simulation state file in a saved world directory.
+pub const STATE_NAME: &str = "simstate.json";
+
+/// Complete state of a running simulation.
+///
+/// Entities are stored as they were at the time of saving, and get remapped to new ones on load.
+#[derive(Serialize, Deserialize)]
+pub struct SimState {
+    /// Current simulation time, measured in simulated months.
+    pub time: u32,
+    /// State of the global RNG at the time of saving.
+    pub rng: GlobalEntropy<WyRand>,
+    /// All polities (including ones without land).
+    pub polities: Vec<(Entity, Polity)>,
+    /// All regions.
+    pub regions: Vec<(Entity, Region)>,
+    /// Extra map data (tile ownership, conflicts, wars, tributes).
+    pub extras: SimMapData,
+    /// Recorded statistics, chronicle and timelapse.
+    pub history: SimHistory,
+}
+
+/// Recorded history of a running simulation.
+///
+/// NOTE: Timelapse frames are not a part of this, they are saved as images next to the state.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SimHistory {
+    /// Recorded statistics of all polities.
+    pub stats: Vec<(Entity, PolityHistory)>,
+    /// All chronicle entries.
+    pub chronicle: Vec<ChronicleEntry>,
+    /// Timelapse without frames.
+    pub timelapse: Timelapse,
+}
+
+impl SimHistory {
+    /// Gather the recorded history.
+    pub fn capture(stats: &SimStats, chronicle: &Chronicle, timelapse: &Timelapse) -> Self {
+        Self {
+            stats: stats.polities.iter().map(|(e, x)| (*e, x.clone())).collect(),
+            chronicle: chronicle.entries.clone(),
+            timelapse: Timelapse {
+                times: timelapse.times.clone(),
+                frames: vec![],
+                width: timelapse.width,
+                height: timelapse.height,
+                thinning: timelapse.thinning,
+            },
+        }
+    }
+
+    /// Replace the recorded history. Timelapse frames have to be loaded separately.
+    pub fn restore(self, stats: &mut SimStats, chronicle: &mut Chronicle, timelapse: &mut Timelapse) {
+        stats.polities = self.stats.into_iter().collect();
+        chronicle.entries = self.chronicle;
+        *timelapse = self.timelapse;
+    }
+
+    fn remap_entities(&mut self, map: &mut impl FnMut(Entity) -> Entity) {
+        for (entity, _) in self.stats.iter_mut() {
+            *entity = map(*entity);
+        }
+        for entry in self.chronicle.iter_mut() {
+            entry.event.remap_entities(&mut *map);
+        }
+    }
+}
+
+impl SimState {
+    /// Gather the current simulation state. The RNG state is copied as is, without advancing it.
+    pub fn capture<'a>(
+        sim: &SimControl,
+        rng: &GlobalEntropy<WyRand>,
+        polities: impl Iterator<Item = (Entity, &'a Polity)>,
+        regions: impl Iterator<Item = (Entity, &'a Region)>,
+        extras: &SimMapData,
+        history: SimHistory,
+    ) -> Self {
+        let mut polities: Vec<_> = polities.map(|(e, x)| (e, x.clone())).collect();
+        polities.sort_by_key(|x| x.0);
+        let mut regions: Vec<_> = regions.map(|(e, x)| (e, x.clone())).collect();
+        regions.sort_by_key(|x| x.0);
+        Self {
+            time: sim.time,
+            rng: rng.clone(),
+            polities,
+            regions,
+            extras: extras.clone(),
+            history,
+        }
+    }
+
+    /// Spawn all saved polities and regions, and replace current simulation data with saved data.
+    /// Returns the saved history, with entities remapped to the spawned ones.
+    pub fn restore(
+        self,
+        config: &AtlasSimConfig,
+        sim: &mut SimControl,
+        extras: &mut SimMapData,
+        rng: &mut GlobalEntropy<WyRand>,
+        commands: &mut Commands,
+    ) -> SimHistory {
+        let Self {
+            time,
+            rng: saved_rng,
+            mut polities,
+            mut regions,
+            extras: mut saved_extras,
+            mut history,
+        } = self;
+        // Map saved entities to new ones in the same relative order,
+        // so that all collections ordered by entity keep their order after loading.
+        let mut saved = BTreeSet::<Entity>::default();
+        remap_all(
+            &mut polities,
+            &mut regions,
+            &mut saved_extras,
+            &mut history,
+            &mut |entity| {
+                saved.insert(entity);
+                entity
+            },
+        );
+        let mut spawned: Vec<_> = saved.iter().map(|_| commands.spawn_empty().id()).collect();
+        spawned.sort();
+        let entity_map: BTreeMap<_, _> = saved.into_iter().zip(spawned).collect();
+        remap_all(
+            &mut polities,
+            &mut regions,
+            &mut saved_extras,
+            &mut history,
+            &mut |entity| entity_map[&entity],
+        );
+        // Rebuild city rtrees.
+        let polity_lookup: BTreeMap<Entity, usize> =
+            polities.iter().enumerate().map(|(i, (e, _))| (*e, i)).collect();
+        for (_, region) in regions.iter() {
+            let pos = config.index_to_map_i(region.city_position);
+            saved_extras.rtree.insert(pos);
+            if let Some(i) = polity_lookup.get(&region.polity) {
+                polities[*i].1.rtree.insert(pos);
+            }
+        }
+        // Spawn everything.
+        for (entity, polity) in polities {
+            commands.entity(entity).insert(polity);
+        }
+        for (entity, mut region) in regions {
+            region.need_visual_update = true;
+            spawn_region_with_city(entity, region.city, region, commands);
+        }
+        *extras = saved_extras;
+        sim.time = time;
+        sim.paused = true;
+        *rng = saved_rng;
+        history
+    }
+}
+
+/// Helper function
+///
+/// Remap all entities referenced by the saved state.
+fn remap_all(
+    polities: &mut [(Entity, Polity)],
+    regions: &mut [(Entity, Region)],
+    extras: &mut SimMapData,
+    history: &mut SimHistory,
+    map: &mut impl FnMut(Entity) -> Entity,
+) {
+    for (entity, polity) in polities.iter_mut() {
+        *entity = map(*entity);
+        polity.remap_entities(&mut *map);
+    }
+    for (entity, region) in regions.iter_mut() {
+        *entity = map(*entity);
+        region.remap_entities(&mut *map);
+    }
+    extras.remap_entities(&mut *map);
+    history.remap_entities(&mut *map);
+}
+
+/// Helper function
+///
+/// Save the world layers, simulation config and simulation state to a directory.
+pub fn save_world_state(
+    base_path: &Path,
+    logics: &MapLogicData,
+    config: &AtlasSimConfig,
+    state: &SimState,
+) -> Result<(), Error> {
+    // Custom layers must not overwrite each other's files.
+    check_custom_layers(&config.custom_layers)?;
+    // Export all layers.
+    let (width, height) = config.get_world_size();
+    for (layer, name) in EXPORT_DATA_LAYERS {
+        let data = logics.get_layer(layer);
+        let path = base_path.join(name);
+        match layer {
+            MapDataLayer::Preview => save_image(path, data, width, height),
+            _ => save_image_grey(path, data, width, height),
+        }?;
+    }
+    // Export custom layers.
+    for (i, info) in config.custom_layers.iter().enumerate() {
+        let data = logics.get_layer(MapDataLayer::Custom(i as u8));
+        save_image_grey(base_path.join(info.file_name()), data, width, height)?;
+    }
+    // Export config and state.
+    save_config(config, base_path.join(CONFIG_NAME))?;
+    save_json(state, base_path.join(STATE_NAME))
+}
+
+/// Helper function
+///
+/// Load the simulation state from a saved world directory.
+pub fn load_world_state(base_path: &Path) -> Result<SimState, Error> {
+    load_json(base_path.join(STATE_NAME))
+}
+
+impl Polity {
+    fn remap_entities(&mut self, map: &mut impl FnMut(Entity) -> Entity) {
+        self.this = self.this.map(&mut *map);
+        self.regions = std::mem::take(&mut self.regions)
+            .into_iter()
+            .map(&mut *map)
+            .collect();
+        let neighbours = std::mem::take(&mut self.neighbours);
+        for (entity, (regions, relations, flip)) in neighbours {
+            let entity = map(entity);
+            let regions = regions.into_iter().map(&mut *map).collect();
+            self.neighbours.insert(entity, (regions, relations, flip));
+        }
+        for tribute in self.tributes.iter_mut().flatten() {
+            tribute.receiver = map(tribute.receiver);
+        }
+    }
+}
+
+impl Region {
+    fn remap_entities(&mut self, map: &mut impl FnMut(Entity) -> Entity) {
+        self.polity = map(self.polity);
+        self.city = map(self.city);
+    }
+}
+
+impl ChronicleEvent {
+    fn remap_entities(&mut self, map: &mut impl FnMut(Entity) -> Entity) {
+        let tags: Vec<&mut PolityTag> = match self {
+            Self::WarDeclared {
+                attacker, defender, ..
+            }
+            | Self::WarConcluded {
+                attacker, defender, ..
+            } => vec![attacker, defender],
+            Self::CityFounded { polity }
+            | Self::GreatPerson { polity, .. }
+            | Self::GreatWork { polity, .. } => {
+                vec![polity]
+            }
+            Self::RegionConquered { from, to } => vec![from, to],
+            Self::PolityDestroyed { polity, by } => vec![polity, by],
+        };
+        for tag in tags {
+            tag.entity = map(tag.entity);
+        }
+    }
+}
+
+impl Conflict {
+    fn remap_entities(&mut self, map: &mut impl FnMut(Entity) -> Entity) {
+        self.primary_attacker = map(self.primary_attacker);
+        self.primary_defender = map(self.primary_defender);
+        for members in [&mut self.attackers, &mut self.defenders] {
+            let old = std::mem::take(members);
+            for (_, mut member) in old {
+                member.entity = map(member.entity);
+                members.insert(member.entity, member);
+            }
+        }
+    }
+}
+
+impl SimMapData {
+    fn remap_entities(&mut self, map: &mut impl FnMut(Entity) -> Entity) {
+        for tile in self
+            .tile_region
+            .iter_mut()
+            .chain(self.tile_polity.iter_mut())
+            .flatten()
+        {
+            *tile = map(*tile);
+        }
+        let deferred = std::mem::take(&mut self.deferred_regions);
+        for (polity, mut vec) in deferred {
+            for (_, region, city) in vec.iter_mut() {
+                *region = map(*region);
+                *city = map(*city);
+            }
+            self.deferred_regions.insert(map(polity), vec);
+        }
+        for conflict in self.conflicts.values_mut() {
+            conflict.remap_entities(&mut *map);
+        }
+        self.war_map = WarMap(
+            std::mem::take(&mut self.war_map.0)
+                .into_iter()
+                .map(|(EntityPair(a, b), v)| (EntityPair::new(map(a), map(b)), v))
+                .collect(),
+        );
+        self.tributes = std::mem::take(&mut self.tributes)
+            .into_iter()
+            .map(|(k, v)| (map(k), v))
+            .collect();
+    }
+}
+
+impl Serialize for WarMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Map keys must be strings in JSON, so store as a list of pairs.
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for WarMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pairs = Vec::<(EntityPair, (u32, u32))>::deserialize(deserializer)?;
+        Ok(Self(pairs.into_iter().collect()))
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::Path};
+
+use atlas_lib::{
+    bevy::{ecs as bevy_ecs, prelude::*},
+    config::{sim::AtlasSimConfig, Error},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::sim::{
+    polity::{Polity, RES_CIVILIAN, RES_CULTURE, RES_MILITARY, RES_RESEARCH, RES_SUPPLY, RES_WEALTH_POPS},
+    region::Region,
+    time_to_string, SimControl,
+};
+
+/// Name of the statistics file written next to a headless save.
+pub const STATS_NAME: &str = "stats.csv";
+
+pub const LEN_STAT: usize = 16;
+/// Total population (including military)
+pub const STAT_POPULATION: usize = 0;
+/// Military
+pub const STAT_MILITARY: usize = 1;
+/// Number of regions
+pub const STAT_REGIONS: usize = 2;
+/// Number of owned tiles
+pub const STAT_TERRITORY: usize = 3;
+/// Supply
+pub const STAT_SUPPLY: usize = 4;
+/// Civilian Industry
+pub const STAT_CIVILIAN: usize = 5;
+/// Military Industry
+pub const STAT_MILITARY_INDU: usize = 6;
+/// Wealth
+pub const STAT_WEALTH: usize = 7;
+/// Research
+pub const STAT_RESEARCH: usize = 8;
+/// Culture
+pub const STAT_CULTURE: usize = 9;
+/// Average stability
+pub const STAT_STABILITY: usize = 10;
+/// Average health
+pub const STAT_HEALTH: usize = 11;
+/// Average region development
+pub const STAT_DEVELOPMENT: usize = 12;
+/// Sum of major technology levels
+pub const STAT_TECH: usize = 13;
+/// Sum of major tradition levels
+pub const STAT_TRADITIONS: usize = 14;
+/// Number of active conflicts
+pub const STAT_CONFLICTS: usize = 15;
+
+pub const STAT_LABELS: [&str; LEN_STAT] = [
+    "Population",
+    "Military",
+    "Regions",
+    "Territory",
+    "Supply",
+    "Civilian Industry",
+    "Military Industry",
+    "Wealth",
+    "Research",
+    "Culture",
+    "Stability",
+    "Health",
+    "Development",
+    "Technology",
+    "Traditions",
+    "Conflicts",
+];
+
+/// Recorded history of all polities.
+#[derive(Resource, Default)]
+pub struct SimStats {
+    pub polities: BTreeMap<Entity, PolityHistory>,
+}
+
+/// Recorded history of a single polity.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct PolityHistory {
+    /// Time of each sample, measured in simulated months.
+    pub times: Vec<u32>,
+    /// Sampled values, one series per statistic.
+    pub series: [Vec<f32>; LEN_STAT],
+}
+
+impl PolityHistory {
+    fn push(&mut self, time: u32, sample: [f32; LEN_STAT]) {
+        self.times.push(time);
+        for (series, value) in self.series.iter_mut().zip(sample) {
+            series.push(value);
+        }
+    }
+}
+
+/// Update system
+///
+/// Sample polity data into the history.
+pub fn update_stats(
+    mut stats: ResMut<SimStats>,
+    sim: Res<SimControl>,
+    config: Res<AtlasSimConfig>,
+    polities: Query<(Entity, &Polity)>,
+    regions: Query<&Region>,
+) {
+    let interval = config.scenario.stats_interval;
+    if interval == 0 || sim.time % interval != 0 {
+        return;
+    }
+    for (entity, polity) in polities.iter() {
+        // Skip polities that are gone.
+        if polity.regions.is_empty() {
+            continue;
+        }
+        let (mut tiles, mut development) = (0, 0.0);
+        for x in polity.regions.iter() {
+            if let Ok(region) = regions.get(*x) {
+                tiles += region.tiles.len();
+                development += region.development;
+            }
+        }
+        let mut sample = [0.0; LEN_STAT];
+        sample[STAT_POPULATION] = polity.population + polity.jobs.military;
+        sample[STAT_MILITARY] = polity.jobs.military;
+        sample[STAT_REGIONS] = polity.regions.len() as f32;
+        sample[STAT_TERRITORY] = tiles as f32;
+        sample[STAT_SUPPLY] = polity.resources[RES_SUPPLY];
+        sample[STAT_CIVILIAN] = polity.resources[RES_CIVILIAN];
+        sample[STAT_MILITARY_INDU] = polity.resources[RES_MILITARY];
+        sample[STAT_WEALTH] = polity.resources[RES_WEALTH_POPS];
+        sample[STAT_RESEARCH] = polity.resources[RES_RESEARCH];
+        sample[STAT_CULTURE] = polity.resources[RES_CULTURE];
+        sample[STAT_STABILITY] = polity.avg_stability;
+        sample[STAT_HEALTH] = polity.avg_health;
+        sample[STAT_DEVELOPMENT] = development / polity.regions.len() as f32;
+        sample[STAT_TECH] = polity.tech.iter().map(|x| x[0]).sum();
+        sample[STAT_TRADITIONS] = polity.traditions.iter().map(|x| x[0]).sum();
+        sample[STAT_CONFLICTS] = polity.conflicts.len() as f32;
+        stats.polities.entry(entity).or_default().push(sim.time, sample);
+    }
+}
+
+/// Helper function
+///
+/// Write the recorded history of all polities to a CSV file, one row per polity per sample.
+pub fn export_stats_csv(stats: &SimStats, path: impl AsRef<Path>) -> Result<(), Error> {
+    let mut csv = String::from("polity,month,date");
+    for label in STAT_LABELS {
+        csv.push(',');
+        csv.push_str(label);
+    }
+    csv.push('\n');
+    for (entity, history) in stats.polities.iter() {
+        for (i, time) in history.times.iter().enumerate() {
+            let _ = write!(csv, "{},{},{}", entity.index(), time, time_to_string(*time));
+            for series in history.series.iter() {
+                let _ = write!(csv, ",{}", series[i]);
+            }
+            csv.push('\n');
+        }
+    }
+    fs::write(path, csv)?;
+    Ok(())
+}
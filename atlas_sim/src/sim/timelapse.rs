@@ -0,0 +1,176 @@
+use std::{fs, path::Path};
+
+use atlas_lib::{
+    bevy::{ecs as bevy_ecs, prelude::*},
+    config::{load_image, save_animation, save_image, sim::AtlasSimConfig, AtlasConfig, Error},
+    domain::{graphics::MapLogicData, map::MapDataLayer},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::sim::{polity::Polity, SimControl, SimMapData};
+
+/// Name of the timelapse directory written next to a headless save.
+pub const TIMELAPSE_DIR: &str = "timelapse";
+/// Name of the animated timelapse file.
+pub const ANIMATION_NAME: &str = "timelapse.png";
+/// How long each frame of the animation is shown, in milliseconds.
+const FRAME_DELAY: u16 = 250;
+/// Opacity of polity colors drawn over the preview layer.
+const POLITY_ALPHA: f32 = 0.75;
+/// Maximum number of frames kept in memory. When exceeded, every other frame is dropped
+/// and the capture interval is doubled.
+const MAX_FRAMES: usize = 240;
+/// Maximum frame width or height, in pixels. Larger maps are downsampled.
+const MAX_FRAME_SIZE: u32 = 1024;
+
+/// Captured frames of the political map.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Timelapse {
+    /// Time of each frame, measured in simulated months.
+    pub times: Vec<u32>,
+    /// RGBA frame data.
+    #[serde(skip)]
+    pub frames: Vec<Vec<u8>>,
+    /// Frame width, in pixels.
+    pub width: u32,
+    /// Frame height, in pixels.
+    pub height: u32,
+    /// How many times the capture interval was doubled to stay under the frame limit.
+    pub thinning: u32,
+}
+
+impl Timelapse {
+    /// Drop every other frame and double the capture interval.
+    fn thin_out(&mut self, interval: u32) {
+        self.thinning += 1;
+        let interval = interval << self.thinning;
+        let mut frames = std::mem::take(&mut self.frames).into_iter();
+        let times = std::mem::take(&mut self.times);
+        for time in times {
+            let frame = frames.next().unwrap();
+            if time % interval == 0 {
+                self.times.push(time);
+                self.frames.push(frame);
+            }
+        }
+    }
+}
+
+/// Update system
+///
+/// Capture the political map every few years.
+pub fn update_timelapse(
+    mut timelapse: ResMut<Timelapse>,
+    sim: Res<SimControl>,
+    config: Res<AtlasSimConfig>,
+    extras: Res<SimMapData>,
+    logics: Res<MapLogicData>,
+    polities: Query<&Polity>,
+) {
+    let interval = config.scenario.timelapse_interval * 12;
+    if interval == 0 || sim.time % (interval << timelapse.thinning) != 0 {
+        return;
+    }
+    let frame = capture_frame(&config, &extras, &logics, &polities);
+    let (width, height) = config.get_world_size();
+    let factor = width.max(height).div_ceil(MAX_FRAME_SIZE);
+    let (frame, width, height) = downsample(frame, width, height, factor);
+    timelapse.width = width;
+    timelapse.height = height;
+    timelapse.times.push(sim.time);
+    timelapse.frames.push(frame);
+    if timelapse.frames.len() > MAX_FRAMES {
+        timelapse.thin_out(interval);
+    }
+}
+
+/// Helper function
+///
+/// Draw polity ownership of all tiles, optionally over the preview layer.
+fn capture_frame(
+    config: &AtlasSimConfig,
+    extras: &SimMapData,
+    logics: &MapLogicData,
+    polities: &Query<&Polity>,
+) -> Vec<u8> {
+    let over_preview = config.scenario.timelapse_preview;
+    let mut frame = if over_preview {
+        logics.get_layer(MapDataLayer::Preview).to_vec()
+    } else {
+        vec![0; extras.tile_polity.len() * 4]
+    };
+    for (tile, polity) in extras.tile_polity.iter().enumerate() {
+        let Some(color) = polity
+            .and_then(|x| polities.get(x).ok())
+            .map(|x| x.color.as_rgba_u8())
+        else {
+            continue;
+        };
+        let pixel = &mut frame[tile * 4..tile * 4 + 4];
+        if over_preview {
+            for (value, color) in pixel.iter_mut().zip(color).take(3) {
+                *value = (*value as f32 * (1.0 - POLITY_ALPHA) + color as f32 * POLITY_ALPHA) as u8;
+            }
+        } else {
+            pixel[..3].copy_from_slice(&color[..3]);
+        }
+        pixel[3] = 255;
+    }
+    frame
+}
+
+/// Helper function
+///
+/// Shrink an RGBA image by an integer factor, keeping every n-th pixel.
+fn downsample(frame: Vec<u8>, width: u32, height: u32, factor: u32) -> (Vec<u8>, u32, u32) {
+    if factor <= 1 {
+        return (frame, width, height);
+    }
+    let (new_width, new_height) = (width.div_ceil(factor), height.div_ceil(factor));
+    let mut result = Vec::with_capacity((new_width * new_height * 4) as usize);
+    for y in (0..height).step_by(factor as usize) {
+        for x in (0..width).step_by(factor as usize) {
+            let i = ((y * width + x) * 4) as usize;
+            result.extend_from_slice(&frame[i..i + 4]);
+        }
+    }
+    (result, new_width, new_height)
+}
+
+/// Helper function
+///
+/// Write all captured frames as numbered PNG files, and as a single animated PNG file.
+pub fn export_timelapse(timelapse: &Timelapse, base_path: &Path) -> Result<(), Error> {
+    if timelapse.frames.is_empty() {
+        return Ok(());
+    }
+    fs::create_dir_all(base_path)?;
+    let (width, height) = (timelapse.width, timelapse.height);
+    for (i, frame) in timelapse.frames.iter().enumerate() {
+        let path = base_path.join(frame_name(i));
+        save_image(path, frame, width, height)?;
+    }
+    save_animation(
+        base_path.join(ANIMATION_NAME),
+        &timelapse.frames,
+        width,
+        height,
+        FRAME_DELAY,
+    )
+}
+
+/// Helper function
+///
+/// Load frames written by `export_timelapse`, one for each recorded frame time.
+pub fn import_timelapse(timelapse: &mut Timelapse, base_path: &Path) -> Result<(), Error> {
+    let (width, height) = (timelapse.width, timelapse.height);
+    timelapse.frames = (0..timelapse.times.len())
+        .map(|i| load_image(base_path.join(frame_name(i)), width, height))
+        .collect::<Result<_, _>>()?;
+    Ok(())
+}
+
+/// Name of the numbered frame file.
+fn frame_name(i: usize) -> String {
+    format!("frame_{:04}.png", i)
+}
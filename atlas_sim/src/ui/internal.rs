@@ -83,6 +83,18 @@ fn import_special(&mut self, path: &Path) {
         self.events.import_start_request = Some(path.into());
     }
 
+    fn save_stats(&mut self, path: &Path) {
+        self.events.export_stats_request = Some(path.into());
+    }
+
+    fn save_chronicle(&mut self, path: &Path) {
+        self.events.export_chronicle_request = Some(path.into());
+    }
+
+    fn save_timelapse(&mut self, path: &Path) {
+        self.events.export_timelapse_request = Some(path.into());
+    }
+
     fn load_layer_data(&mut self, _path: &Path, _layer: MapDataLayer) {
         unreachable!()
     }
@@ -94,4 +106,12 @@ fn save_layer_data(&mut self, _path: &Path, _layer: MapDataLayer) {
     fn render_image(&mut self, _path: &Path, _layer: MapDataLayer) {
         unreachable!()
     }
+
+    fn export_gis(&mut self, _path: &Path) {
+        unreachable!()
+    }
+
+    fn export_mesh(&mut self, _path: &Path) {
+        unreachable!()
+    }
 }
@@ -6,8 +6,8 @@
     base::{
         events::EventStruct,
         ui::{
-            open_file_dialog, update_viewport, FileDialogMode, HandleFileDialog, MainCamera, UiCreator,
-            UiPluginBase, UiStateBase, UiUpdate,
+            layer_dropdown, open_file_dialog, update_viewport, FileDialogMode, HandleFileDialog, MainCamera,
+            UiCreator, UiPluginBase, UiStateBase, UiUpdate,
         },
     },
     bevy::{app::AppExit, ecs as bevy_ecs, prelude::*, window::PrimaryWindow},
@@ -17,20 +17,18 @@
     },
     config::{sim::AtlasSimConfig, AtlasConfig},
     domain::{graphics::CurrentWorldModel, map::MapDataOverlay},
-    ui::{
-        button, button_action, button_action_enabled,
-        sidebar::{SidebarControl, SidebarEnumDropdown, SidebarPanel},
-        window,
-    },
+    ui::{button, button_action, button_action_enabled, sidebar::SidebarPanel, window},
 };
 use internal::{reset_config_clicked, reset_panel_clicked, FileDialogHandler};
 use panel_init::*;
 use panel_sim::*;
 
 use crate::sim::{
+    chronicle::{Chronicle, ChronicleEntry},
     conflict::ConflictMarker,
     polity::Polity,
     region::Region,
+    stats::{PolityHistory, SimStats},
     ui::{ConflictUi, PolityUi, RegionUi},
     SimControl, SimMapData,
 };
@@ -46,7 +44,10 @@ fn build(&self, app: &mut App) {
                 UiUpdate,
                 (update_ui, update_viewport, update_location, update_click_location).chain(),
             )
-            .add_systems(UiUpdate, update_selection_data);
+            .add_systems(
+                UiUpdate,
+                (update_selection_data, update_chronicle_data, update_focus),
+            );
     }
 }
 
@@ -67,6 +68,7 @@ fn update_ui(
         return;
     }
     ui_state.sim_control = sim_control.clone();
+    ui_base.custom_layers = config.get_custom_layers().into_iter().map(|x| x.name).collect();
     ui_state.create_ui(
         contexts.ctx_mut(),
         &mut config,
@@ -83,6 +85,8 @@ pub struct Selection {
     pub polity: Option<PolityUi>,
     pub region: Option<RegionUi>,
     pub conflict: Option<ConflictUi>,
+    /// Recorded history of the selected polity. Only copied again when new samples are recorded.
+    pub history: Option<(Entity, PolityHistory)>,
 }
 
 #[derive(Resource)]
@@ -101,6 +105,10 @@ pub struct AtlasSimUi {
     pub selection: Option<Selection>,
     /// Has a world been loaded?
     pub world_loaded: bool,
+    /// Chronicle copy.
+    pub chronicle: Vec<ChronicleEntry>,
+    /// Map tile that should be focused on and selected.
+    pub focus_request: Option<u32>,
 }
 
 impl Default for AtlasSimUi {
@@ -113,10 +121,21 @@ fn default() -> Self {
             force_changed: false,
             selection: None,
             world_loaded: false,
+            chronicle: vec![],
+            focus_request: None,
         }
     }
 }
 
+impl AtlasSimUi {
+    /// Leave the setup mode and show simulation info panels.
+    pub fn enter_simulation_mode(&mut self) {
+        self.setup_mode = false;
+        self.current_panel = Box::<InfoPanelPolity>::default();
+        self.force_changed = true;
+    }
+}
+
 impl UiCreator<AtlasSimConfig> for AtlasSimUi {
     fn create_sidebar_head(
         &mut self,
@@ -133,14 +152,21 @@ fn create_sidebar_head(
                     button_action_enabled(ui, "Import Generated World", self.setup_mode, || {
                         open_file_dialog(ui_base, FileDialogMode::ImportSpecial)
                     });
-                    /*
-                    button_action_enabled(ui, "Import World State", false, || {
+                    button_action_enabled(ui, "Import World State", self.setup_mode, || {
                         open_file_dialog(ui_base, FileDialogMode::Import)
                     });
-                    button_action_enabled(ui, "Export World State", false, || {
+                    button_action_enabled(ui, "Export World State", !self.setup_mode, || {
                         open_file_dialog(ui_base, FileDialogMode::Export)
                     });
-                    */
+                    button_action_enabled(ui, "Export Statistics", !self.setup_mode, || {
+                        open_file_dialog(ui_base, FileDialogMode::SaveStats)
+                    });
+                    button_action_enabled(ui, "Export Chronicle", !self.setup_mode, || {
+                        open_file_dialog(ui_base, FileDialogMode::SaveChronicle)
+                    });
+                    button_action_enabled(ui, "Export Timelapse", !self.setup_mode, || {
+                        open_file_dialog(ui_base, FileDialogMode::SaveTimelapse)
+                    });
                     button_action(ui, "Exit", || {
                         exit.send(AppExit);
                     });
@@ -176,7 +202,6 @@ fn create_layer_view_settings(
         events: &mut EventStruct,
     ) {
         // Layer visibility dropdown.
-        // NOTE: `ui.horizontal_wrapped()` respects `ui.end_row()` used internally by a `SidebarControl`.
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
                 let label = match (self.setup_mode, self.sim_control.paused) {
@@ -201,11 +226,8 @@ fn create_layer_view_settings(
             });
             ui.separator();
             ui.horizontal(|ui| {
-                let old = ui_base.current_layer;
-                let selection = SidebarEnumDropdown::new(ui, "Layer", &mut ui_base.current_layer).show(None);
-                SidebarEnumDropdown::post_show(selection, &mut ui_base.current_layer);
                 // Trigger layer change event as needed.
-                if old != ui_base.current_layer {
+                if layer_dropdown(ui, ui_base) {
                     events.viewed_layer_changed = Some(ui_base.current_layer);
                 }
                 ui_base.overlay_window_open |= button(ui, "Overlays");
@@ -247,6 +269,10 @@ macro_rules! tab {
                     tab!("Culture", InfoPanelCulture, ui);
                     tab!("Combat", InfoPanelCombat, ui);
                 });
+                egui::menu::bar(ui, |ui| {
+                    tab!("Statistics", InfoPanelStats, ui);
+                    tab!("Chronicle", InfoPanelChronicle, ui);
+                });
             }
             if changed || self.force_changed {
                 self.force_changed = false;
@@ -383,6 +409,7 @@ fn update_click_location(
                     polity: None,
                     region: None,
                     conflict: None,
+                    history: None,
                 });
             }
         }
@@ -398,6 +425,7 @@ fn update_selection_data(
     regions: Query<&Region>,
     conflicts: Query<&ConflictMarker>,
     extras: Res<SimMapData>,
+    stats: Res<SimStats>,
     config: Res<AtlasSimConfig>,
 ) {
     let selection = if let Some(selection) = &mut ui_state.selection {
@@ -415,8 +443,19 @@ fn update_selection_data(
     } else {
         None
     };
-    if let Ok(polity) = polities.get(polity.unwrap_or(selection.entity)) {
+    let polity_entity = polity.unwrap_or(selection.entity);
+    if let Ok(polity) = polities.get(polity_entity) {
         selection.polity = Some(polity.as_ui(&config, &extras));
+        let stale = |(entity, old): &(Entity, PolityHistory), new: &PolityHistory| {
+            *entity != polity_entity || old.times.len() != new.times.len()
+        };
+        match stats.polities.get(&polity_entity) {
+            Some(history) if selection.history.as_ref().map_or(true, |x| stale(x, history)) => {
+                selection.history = Some((polity_entity, history.clone()));
+            }
+            Some(_) => {}
+            None => selection.history = None,
+        }
     }
 }
 
@@ -431,3 +470,47 @@ pub fn new(overlay: MapDataOverlay) -> Self {
         Self { overlay }
     }
 }
+
+/// Update system
+///
+/// Copy new chronicle entries for the UI.
+fn update_chronicle_data(mut ui_state: ResMut<AtlasSimUi>, chronicle: Res<Chronicle>) {
+    let len = ui_state.chronicle.len();
+    if len > chronicle.entries.len() {
+        // The chronicle has been reset.
+        ui_state.chronicle.clear();
+    } else if len < chronicle.entries.len() {
+        ui_state.chronicle.extend_from_slice(&chronicle.entries[len..]);
+    }
+}
+
+/// Update system
+///
+/// Move the camera to the requested tile and select whatever is there.
+fn update_focus(
+    mut ui_state: ResMut<AtlasSimUi>,
+    ui_base: Res<UiStateBase>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+    extras: Res<SimMapData>,
+    config: Res<AtlasSimConfig>,
+) {
+    let Some(i) = ui_state.focus_request.take() else {
+        return;
+    };
+    // Only move the camera over a flat map.
+    if !ui_base.camera.rotate_mode {
+        let (x, y) = config.index_to_world(i);
+        let mut camera = camera.single_mut();
+        camera.translation.x = x;
+        camera.translation.y = y;
+    }
+    if let Some(entity) = extras.tile_region.get(i as usize).copied().flatten() {
+        ui_state.selection = Some(Selection {
+            entity,
+            polity: None,
+            region: None,
+            conflict: None,
+            history: None,
+        });
+    }
+}
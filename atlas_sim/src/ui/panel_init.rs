@@ -1,7 +1,7 @@
 use atlas_lib::{
     base::events::EventStruct,
     bevy_egui::egui::Ui,
-    config::sim::{AtlasSimConfig, PolityConfig, StartingPoint},
+    config::sim::AtlasSimConfig,
     domain::map::MapDataLayer,
     ui::{
         button, button_action_enabled,
@@ -9,7 +9,7 @@
     },
 };
 
-use crate::ui::{panel_sim::InfoPanelPolity, AtlasSimUi};
+use crate::{map::ensure_starting_points, ui::AtlasSimUi};
 
 macro_rules! make_panel {
     ($panel:ident, $name:literal, $field:ident) => {
@@ -40,7 +40,7 @@ fn get_layer(&self) -> MapDataLayer {
 impl SidebarPanel<AtlasSimConfig, AtlasSimUi> for MainPanelScenario {
     fn make_ui(&mut self, ui: &mut Ui, config: &mut AtlasSimConfig) {
         config.scenario.make_ui(ui);
-        Self::ensure_starting_points(config);
+        ensure_starting_points(config);
     }
 
     fn extra_ui_pre(
@@ -52,9 +52,7 @@ fn extra_ui_pre(
     ) {
         button_action_enabled(ui, "Begin Simulation", ui_state.world_loaded, || {
             events.simulation_start_request = Some(());
-            ui_state.setup_mode = false;
-            ui_state.current_panel = Box::<InfoPanelPolity>::default();
-            ui_state.force_changed = true;
+            ui_state.enter_simulation_mode();
         });
         if button(ui, "Randomize Starting Points") {
             events.randomize_starts_request = Some(());
@@ -70,31 +68,6 @@ fn get_layer(&self) -> MapDataLayer {
     }
 }
 
-impl MainPanelScenario {
-    fn ensure_starting_points(config: &mut AtlasSimConfig) {
-        let diff = config.scenario.num_starts as i32 - config.scenario.start_points.len() as i32;
-        if diff >= 0 {
-            for _ in 0..diff as usize {
-                config.scenario.start_points.push(StartingPoint {
-                    polity: PolityConfig {
-                        population: config.scenario.start_pop,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                });
-            }
-        } else {
-            for _ in 0..(-diff as usize) {
-                config.scenario.start_points.pop();
-            }
-        }
-        for point in &mut config.scenario.start_points {
-            point.position[0] = point.position[0].clamp(0, config.general.world_size[0] - 1);
-            point.position[1] = point.position[1].clamp(0, config.general.world_size[1] - 1);
-        }
-    }
-}
-
 /// Panel with climate generation settings.
 #[derive(Default, Clone, Copy)]
 pub struct MainPanelClimate;
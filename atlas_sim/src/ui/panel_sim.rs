@@ -1,12 +1,19 @@
 use atlas_lib::{
     base::events::EventStruct,
-    bevy_egui::egui::{Grid, Ui},
+    bevy_egui::egui::{self, Grid, Ui},
     config::sim::AtlasSimConfig,
     domain::map::MapDataLayer,
+    egui_plot::{Line, Plot, PlotPoints},
     ui::sidebar::{MakeUi, SidebarPanel},
 };
 
-use crate::ui::AtlasSimUi;
+use crate::{
+    sim::{
+        chronicle::{CHR_LABELS, LEN_CHR},
+        stats::STAT_LABELS,
+    },
+    ui::AtlasSimUi,
+};
 
 macro_rules! make_panel {
     ($panel:ident, $name:literal, $fun:ident) => {
@@ -92,3 +99,148 @@ fn get_layer(&self) -> MapDataLayer {
 make_panel!(InfoPanelScience, "Science", make_ui_science);
 make_panel!(InfoPanelCulture, "Culture", make_ui_culture);
 make_panel!(InfoPanelCombat, "Combat", make_ui_combat);
+
+#[derive(Default, Clone, Copy)]
+pub struct InfoPanelStats {
+    stat: usize,
+}
+
+impl SidebarPanel<AtlasSimConfig, AtlasSimUi> for InfoPanelStats {
+    fn extra_ui(
+        &mut self,
+        ui: &mut Ui,
+        config: &mut AtlasSimConfig,
+        ui_state: &mut AtlasSimUi,
+        _events: &mut EventStruct,
+    ) {
+        Grid::new(format!("{}_panel", self.get_heading())).show(ui, |ui| {
+            ui.label("Statistic");
+            egui::ComboBox::from_id_source("stats_combo")
+                .selected_text(STAT_LABELS[self.stat])
+                .show_ui(ui, |ui| {
+                    for (i, label) in STAT_LABELS.iter().enumerate() {
+                        ui.selectable_value(&mut self.stat, i, *label);
+                    }
+                });
+            ui.end_row();
+            ui.label("Sampling Interval [months]")
+                .on_hover_text("How often statistics are recorded. 0 disables recording.");
+            ui.add(egui::DragValue::new(&mut config.scenario.stats_interval).clamp_range(0..=1200));
+            ui.end_row();
+        });
+        if let Some(selection) = &ui_state.selection {
+            if let Some((_, history)) = &selection.history {
+                // Plot against years since the start of the simulation.
+                let points: PlotPoints = history
+                    .times
+                    .iter()
+                    .zip(history.series[self.stat].iter())
+                    .map(|(t, v)| [*t as f64 / 12.0 + 1.0, *v as f64])
+                    .collect();
+                Plot::new("stats_plot")
+                    .height(300.0)
+                    .allow_scroll(false)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(points).name(STAT_LABELS[self.stat]));
+                    });
+                return;
+            }
+        }
+        ui.label("No object selected.");
+    }
+
+    fn get_heading(&self) -> &'static str {
+        "Statistics"
+    }
+
+    fn get_layer(&self) -> MapDataLayer {
+        MapDataLayer::Preview
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct InfoPanelChronicle {
+    /// Which event types to show.
+    filter: [bool; LEN_CHR],
+    /// Show only events involving the selected polity.
+    selected_only: bool,
+}
+
+impl Default for InfoPanelChronicle {
+    fn default() -> Self {
+        Self {
+            filter: [true; LEN_CHR],
+            selected_only: false,
+        }
+    }
+}
+
+impl SidebarPanel<AtlasSimConfig, AtlasSimUi> for InfoPanelChronicle {
+    fn extra_ui(
+        &mut self,
+        ui: &mut Ui,
+        _config: &mut AtlasSimConfig,
+        ui_state: &mut AtlasSimUi,
+        _events: &mut EventStruct,
+    ) {
+        Grid::new(format!("{}_panel", self.get_heading())).show(ui, |ui| {
+            for (i, label) in CHR_LABELS.iter().enumerate() {
+                ui.checkbox(&mut self.filter[i], *label);
+                if i % 2 == 1 {
+                    ui.end_row();
+                }
+            }
+            ui.end_row();
+            ui.checkbox(&mut self.selected_only, "Selected Polity Only")
+                .on_hover_text("Show only events involving the currently selected polity.");
+            ui.end_row();
+        });
+        ui.separator();
+        let polity = ui_state
+            .selection
+            .as_ref()
+            .and_then(|x| x.polity.as_ref())
+            .map(|x| x.this);
+        // Newest entries first.
+        let entries: Vec<_> = ui_state
+            .chronicle
+            .iter()
+            .rev()
+            .filter(|x| self.filter[x.event.kind()])
+            .filter(|x| !self.selected_only || polity.is_some_and(|p| x.event.involves(p)))
+            .collect();
+        if entries.is_empty() {
+            ui.label("Nothing happened yet.");
+            return;
+        }
+        let mut focus = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        egui::ScrollArea::vertical()
+            .max_height(500.0)
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, entries.len(), |ui, range| {
+                for entry in &entries[range] {
+                    let response = ui.add_enabled(
+                        entry.position.is_some(),
+                        egui::Label::new(entry.describe())
+                            .truncate(true)
+                            .sense(egui::Sense::click()),
+                    );
+                    if response.on_hover_text("Click to show on the map.").clicked() {
+                        focus = entry.position;
+                    }
+                }
+            });
+        if focus.is_some() {
+            ui_state.focus_request = focus;
+        }
+    }
+
+    fn get_heading(&self) -> &'static str {
+        "Chronicle"
+    }
+
+    fn get_layer(&self) -> MapDataLayer {
+        MapDataLayer::Preview
+    }
+}